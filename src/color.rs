@@ -0,0 +1,395 @@
+//! Planetarium
+//! ===========
+//!
+//! Canvas color rendering definitions
+//! ----------------------------------
+//!
+//! Defines the public canvas color mode enum `ColorMode`,
+//! the Bayer color filter array pattern enum `BayerPattern`
+//! and the light spot color weights structure `SpotColor`.
+
+/// Light spot color weights
+///
+/// Contains the relative spectral weights of the light spot
+/// in the red, green and blue color channels.
+///
+/// The spot color weights are multiplied with the spot peak intensity
+/// when rendering the color channel samples.
+///
+/// Basic operations
+/// ----------------
+///
+/// ```
+/// use planetarium::SpotColor;
+///
+/// // Neutral white color: all channel weights are 1.0.
+/// let c1 = SpotColor::default();
+///
+/// // Reddish color
+/// let c2 = SpotColor::new(1.0, 0.6, 0.3);
+///
+/// // Check the resulting string representation.
+/// assert_eq!(c2.to_string(), "(1, 0.6, 0.3)");
+/// ```
+///
+/// Conversions
+/// -----------
+///
+/// ```
+/// # use planetarium::SpotColor;
+/// // From a tuple of R, G, B weights
+/// let c1 = SpotColor::from((0.5, 0.8, 1.0));
+///
+/// // From an array of R, G, B weights
+/// let c2 = SpotColor::from([0.5, 0.8, 1.0]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotColor {
+    /// Red channel weight
+    pub r: f32,
+    /// Green channel weight
+    pub g: f32,
+    /// Blue channel weight
+    pub b: f32,
+}
+
+/// Bayer color filter array (CFA) pattern
+///
+/// Defines the color channel layout of the 2x2 pixel mosaic cell
+/// starting from the canvas origin. The pattern is named
+/// by the color channels of the mosaic cell pixels in the row-major order.
+///
+/// Basic operations
+/// ----------------
+///
+/// ```
+/// use planetarium::BayerPattern;
+///
+/// let p = BayerPattern::Rggb;
+///
+/// // Check the resulting string representation.
+/// assert_eq!(p.to_string(), "RGGB");
+///
+/// // The pattern of the mosaic cropped at an odd X offset
+/// assert_eq!(p.at(1, 0), BayerPattern::Grbg);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BayerPattern {
+    /// Red, green / green, blue
+    Rggb,
+    /// Blue, green / green, red
+    Bggr,
+    /// Green, red / blue, green
+    Grbg,
+    /// Green, blue / red, green
+    Gbrg,
+}
+
+/// Canvas color rendering mode
+///
+/// Basic operations
+/// ----------------
+///
+/// ```
+/// use planetarium::{BayerPattern, Canvas, ColorMode, SpotColor, SpotShape};
+///
+/// let mut c = Canvas::new(256, 256);
+///
+/// // Emulate a color sensor with a RGGB Bayer filter mosaic.
+/// c.set_color_mode(ColorMode::Bayer(BayerPattern::Rggb));
+///
+/// let spot = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.5);
+///
+/// // Make the light spot reddish.
+/// c.set_spot_color(spot, SpotColor::new(1.0, 0.6, 0.3));
+///
+/// // Paint the mosaicked light spots.
+/// c.draw();
+///
+/// // Render a full color image instead.
+/// c.set_color_mode(ColorMode::Rgb);
+/// c.draw();
+///
+/// // The pixel buffer contains interleaved R, G, B samples.
+/// assert_eq!(c.pixels().len(), 3 * 256 * 256);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ColorMode {
+    /// Grayscale image: the spot color weights are ignored
+    #[default]
+    Monochrome,
+    /// Raw color sensor image mosaicked with the Bayer CFA pattern
+    Bayer(BayerPattern),
+    /// Full color image with interleaved R, G, B channel samples
+    Rgb,
+}
+
+/// Effective wavelengths of the R, G, B color channels in micrometers
+const CHANNEL_WAVELENGTHS: [f32; 3] = [0.61, 0.55, 0.465];
+
+/// Second radiation constant `hc/k` in micrometer-kelvins
+const PLANCK_C2: f32 = 14388.0;
+
+/// White point color temperature in kelvins
+const WHITE_POINT_TEMP: f32 = 6500.0;
+
+/// Supported B-V color index range
+const BV_RANGE: (f32, f32) = (-0.4, 2.0);
+
+/// Color channel index: red
+pub(crate) const CHANNEL_R: usize = 0;
+
+/// Color channel index: green
+pub(crate) const CHANNEL_G: usize = 1;
+
+/// Color channel index: blue
+pub(crate) const CHANNEL_B: usize = 2;
+
+impl Default for SpotColor {
+    fn default() -> Self {
+        SpotColor {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        }
+    }
+}
+
+impl From<(f32, f32, f32)> for SpotColor {
+    fn from(rgb: (f32, f32, f32)) -> Self {
+        SpotColor::new(rgb.0, rgb.1, rgb.2)
+    }
+}
+
+impl From<[f32; 3]> for SpotColor {
+    fn from(rgb: [f32; 3]) -> Self {
+        SpotColor::new(rgb[0], rgb[1], rgb[2])
+    }
+}
+
+impl std::fmt::Display for SpotColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.r, self.g, self.b)
+    }
+}
+
+impl std::fmt::Display for BayerPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            BayerPattern::Rggb => "RGGB",
+            BayerPattern::Bggr => "BGGR",
+            BayerPattern::Grbg => "GRBG",
+            BayerPattern::Gbrg => "GBRG",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl SpotColor {
+    /// Creates a new light spot color from the R, G, B channel weights.
+    #[must_use]
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        SpotColor { r, g, b }
+    }
+
+    /// Creates a new light spot color approximating the color of a star
+    /// with the B-V color index `bv`.
+    ///
+    /// The star effective temperature is estimated using the Ballesteros
+    /// formula and the color channel weights are sampled from the Planck
+    /// black body spectrum at the 610 nm (R), 550 nm (G) and 465 nm (B)
+    /// wavelengths. The weights are white balanced to the 6500 K black body
+    /// and normalized to the brightest channel weight of 1.0.
+    ///
+    /// The B-V color index is clamped to the -0.4..2.0 range.
+    ///
+    /// ```
+    /// use planetarium::SpotColor;
+    ///
+    /// // Bluish white hot star
+    /// let c = SpotColor::from_bv(-0.2);
+    /// assert!(c.b == 1.0 && c.r < c.g);
+    ///
+    /// // Reddish cool star
+    /// let c = SpotColor::from_bv(1.6);
+    /// assert!(c.r == 1.0 && c.b < c.g);
+    /// ```
+    #[must_use]
+    pub fn from_bv(bv: f32) -> Self {
+        let bv = bv.clamp(BV_RANGE.0, BV_RANGE.1);
+
+        // Ballesteros (2012) effective temperature estimate
+        let temp = 4600.0 * ((0.92 * bv + 1.7).recip() + (0.92 * bv + 0.62).recip());
+
+        // Black body spectral radiance with the constant factors omitted
+        let planck = |lambda: f32, temp: f32| {
+            (lambda.powi(5) * ((PLANCK_C2 / (lambda * temp)).exp() - 1.0)).recip()
+        };
+
+        let [r, g, b] = CHANNEL_WAVELENGTHS.map(|l| planck(l, temp) / planck(l, WHITE_POINT_TEMP));
+        let max = r.max(g).max(b);
+
+        SpotColor::new(r / max, g / max, b / max)
+    }
+
+    /// Returns the color weight for the color channel index.
+    #[must_use]
+    pub(crate) fn channel(&self, index: usize) -> f32 {
+        match index {
+            CHANNEL_R => self.r,
+            CHANNEL_G => self.g,
+            _ => self.b,
+        }
+    }
+}
+
+impl ColorMode {
+    /// Returns the number of color channel samples per pixel.
+    #[must_use]
+    pub(crate) fn channels(self) -> usize {
+        match self {
+            ColorMode::Monochrome | ColorMode::Bayer(_) => 1,
+            ColorMode::Rgb => 3,
+        }
+    }
+}
+
+impl BayerPattern {
+    /// Returns the pattern of the mosaic cropped at the origin `(x, y)`.
+    ///
+    /// The pattern of a window image exported from a Bayer canvas
+    /// depends on the parity of the window origin coordinates.
+    #[must_use]
+    pub fn at(self, x: u32, y: u32) -> BayerPattern {
+        let mut pattern = self;
+
+        // Swap the mosaic cell columns.
+        if x % 2 == 1 {
+            pattern = match pattern {
+                BayerPattern::Rggb => BayerPattern::Grbg,
+                BayerPattern::Grbg => BayerPattern::Rggb,
+                BayerPattern::Bggr => BayerPattern::Gbrg,
+                BayerPattern::Gbrg => BayerPattern::Bggr,
+            };
+        }
+
+        // Swap the mosaic cell rows.
+        if y % 2 == 1 {
+            pattern = match pattern {
+                BayerPattern::Rggb => BayerPattern::Gbrg,
+                BayerPattern::Gbrg => BayerPattern::Rggb,
+                BayerPattern::Bggr => BayerPattern::Grbg,
+                BayerPattern::Grbg => BayerPattern::Bggr,
+            };
+        }
+
+        pattern
+    }
+
+    /// Returns the color channel index of the canvas pixel at `(x, y)`.
+    #[must_use]
+    pub(crate) fn channel(self, x: u32, y: u32) -> usize {
+        let cell = match self {
+            BayerPattern::Rggb => [[CHANNEL_R, CHANNEL_G], [CHANNEL_G, CHANNEL_B]],
+            BayerPattern::Bggr => [[CHANNEL_B, CHANNEL_G], [CHANNEL_G, CHANNEL_R]],
+            BayerPattern::Grbg => [[CHANNEL_G, CHANNEL_R], [CHANNEL_B, CHANNEL_G]],
+            BayerPattern::Gbrg => [[CHANNEL_G, CHANNEL_B], [CHANNEL_R, CHANNEL_G]],
+        };
+
+        cell[(y % 2) as usize][(x % 2) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_colors() {
+        let c1 = SpotColor::default();
+        assert_eq!(c1.to_string(), "(1, 1, 1)");
+
+        let c2 = SpotColor::from((0.5, 0.25, 2.0));
+        assert_eq!(c2.to_string(), "(0.5, 0.25, 2)");
+        assert_eq!(c2, SpotColor::from([0.5, 0.25, 2.0]));
+
+        assert_eq!(c2.channel(CHANNEL_R), 0.5);
+        assert_eq!(c2.channel(CHANNEL_G), 0.25);
+        assert_eq!(c2.channel(CHANNEL_B), 2.0);
+    }
+
+    #[test]
+    fn star_colors() {
+        // Hot O/B type star
+        let c = SpotColor::from_bv(-0.3);
+        assert_eq!(c.b, 1.0);
+        assert!(c.r < c.g && c.g < c.b && c.r > 0.5, "{c}");
+
+        // Sun-like G2 type star is almost white.
+        let c = SpotColor::from_bv(0.65);
+        assert_eq!(c.r, 1.0);
+        assert!(c.b < c.g && c.b > 0.8, "{c}");
+
+        // Cool M type star
+        let c = SpotColor::from_bv(1.8);
+        assert_eq!(c.r, 1.0);
+        assert!(c.b < c.g && c.g < 0.8 && c.b < 0.5, "{c}");
+
+        // The B-V color index is clamped.
+        assert_eq!(SpotColor::from_bv(5.0), SpotColor::from_bv(2.0));
+        assert_eq!(SpotColor::from_bv(-3.0), SpotColor::from_bv(-0.4));
+    }
+
+    #[test]
+    fn bayer_channels() {
+        let p = BayerPattern::Rggb;
+
+        assert_eq!(p.channel(0, 0), CHANNEL_R);
+        assert_eq!(p.channel(1, 0), CHANNEL_G);
+        assert_eq!(p.channel(0, 1), CHANNEL_G);
+        assert_eq!(p.channel(1, 1), CHANNEL_B);
+        assert_eq!(p.channel(6, 4), CHANNEL_R);
+        assert_eq!(p.channel(7, 9), CHANNEL_B);
+
+        let p = BayerPattern::Gbrg;
+
+        assert_eq!(p.channel(0, 0), CHANNEL_G);
+        assert_eq!(p.channel(1, 0), CHANNEL_B);
+        assert_eq!(p.channel(0, 1), CHANNEL_R);
+        assert_eq!(p.channel(1, 1), CHANNEL_G);
+    }
+
+    #[test]
+    fn shift_bayer_patterns() {
+        for p in [
+            BayerPattern::Rggb,
+            BayerPattern::Bggr,
+            BayerPattern::Grbg,
+            BayerPattern::Gbrg,
+        ] {
+            assert_eq!(p.at(0, 0), p);
+            assert_eq!(p.at(2, 4), p);
+
+            // The shifted pattern must describe the same mosaic.
+            for (x, y) in [(1, 0), (0, 1), (1, 1), (3, 6)] {
+                let s = p.at(x, y);
+
+                for (i, j) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    assert_eq!(s.channel(i, j), p.channel(x + i, y + j));
+                }
+            }
+        }
+
+        assert_eq!(BayerPattern::Rggb.at(1, 1), BayerPattern::Bggr);
+        assert_eq!(BayerPattern::Grbg.to_string(), "GRBG");
+    }
+
+    #[test]
+    fn color_mode_channels() {
+        assert_eq!(ColorMode::default().channels(), 1);
+        assert_eq!(ColorMode::Bayer(BayerPattern::Bggr).channels(), 1);
+        assert_eq!(ColorMode::Rgb.channels(), 3);
+    }
+}
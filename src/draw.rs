@@ -7,14 +7,29 @@
 //! Contains private types and implementations of private methods
 //! for the existing public types.
 
-use super::{Canvas, Pixel, Point, SpotId, SpotShape, Vector};
+mod simd;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{Canvas, CanvasPixel, ColorMode, Point, SpotColor, SpotId, SpotShape, Vector, Window};
 use crate::pattern::AiryPattern;
 
+/// Maximum number of tracked dirty canvas regions
+const MAX_DIRTY_REGIONS: usize = 64;
+
+/// Spot pattern row evaluation chunk length in pixels
+const ROW_CHUNK: u32 = 64;
+
+/// Parallel rendering horizontal band height in pixels
+#[cfg(feature = "rayon")]
+const BAND_HEIGHT: u32 = 16;
+
 impl SpotShape {
     /// Calculates the effective radius of the spot image
     /// projected onto the coordinate axes as XY components.
     #[must_use]
-    fn effective_radius_xy(&self) -> (f32, f32) {
+    pub(crate) fn effective_radius_xy(&self) -> (f32, f32) {
         // Rx = F*sqrt(a11^2 + a12^2), Ry = F*sqrt(a22^2 + a21^2))
         (
             AiryPattern::SIZE_FACTOR * self.xx.hypot(self.xy),
@@ -59,8 +74,8 @@ impl SpotShape {
 }
 
 /// Spot bounding box coordinates in pixels
-#[derive(Debug, Clone, Copy)]
-struct BoundingBox {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BoundingBox {
     /// Top left corner X (inclusive)
     x0: u32,
     /// Top left corner Y (inclusive)
@@ -98,80 +113,526 @@ impl BoundingBox {
     /// Checks if the bounding box is contains no pixels.
     #[must_use]
     fn is_empty(&self) -> bool {
-        self.x0 == self.x1 || self.y0 == self.y1
+        self.x0 >= self.x1 || self.y0 >= self.y1
     }
-}
 
-#[allow(
-    clippy::cast_possible_wrap,
-    clippy::cast_possible_truncation,
-    clippy::cast_precision_loss,
-    clippy::cast_sign_loss
-)]
-impl Canvas {
-    /// Draws a single light spot image on the canvas.
-    pub(super) fn draw_spot(&mut self, spot_id: SpotId) {
-        let position = self.spot_position(spot_id).unwrap();
-        let intensity = self.spot_intensity(spot_id).unwrap();
+    /// Calculates the intersection of two bounding boxes.
+    ///
+    /// The intersection of disjoint bounding boxes is empty.
+    #[must_use]
+    fn intersect(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+        }
+    }
 
-        let shape = self.spots[spot_id].shape;
-        let shape_inv = self.spots[spot_id].shape_inv;
+    /// Calculates the bounding box enclosing two bounding boxes.
+    #[must_use]
+    fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+}
 
-        // Fast path for dark spots
-        if intensity <= 0.0 {
-            return;
+impl From<Window> for BoundingBox {
+    fn from(window: Window) -> Self {
+        BoundingBox {
+            x0: window.x,
+            y0: window.y,
+            x1: window.x + window.w,
+            y1: window.y + window.h,
         }
+    }
+}
 
-        let bbox = BoundingBox::new(position, &shape, self.width, self.height);
+/// Light spot rasterization parameters
+#[derive(Debug, Clone, Copy)]
+struct SpotRaster {
+    /// Canvas coordinates of the spot centroid
+    position: Point,
+    /// Inverted spot shape matrix
+    shape_inv: SpotShape,
+    /// Effective peak intensity
+    intensity: f32,
+    /// Spot color channel weights
+    color: SpotColor,
+    /// Spot bounding box clipped to the frame dimensions
+    bbox: BoundingBox,
+}
 
-        // Check is the spot is clipped out of the canvas.
-        if bbox.is_empty() {
-            return;
-        }
+#[allow(clippy::cast_precision_loss)]
+impl SpotRaster {
+    /// Rasterizes the light spot image into a sample buffer
+    /// with `stride` samples per row.
+    ///
+    /// Calls `add(offset, value)` for each light spot sample
+    /// with the sample buffer offset and the linear light sample value.
+    fn render(
+        &self,
+        pattern: &AiryPattern,
+        color_mode: ColorMode,
+        stride: usize,
+        mut add: impl FnMut(usize, f32),
+    ) {
+        let (intensity, color) = (self.intensity, self.color);
+        let bbox = &self.bbox;
+
+        // Number of interleaved color channel samples per pixel
+        let channels = color_mode.channels();
+
+        // Spot pattern intensity row chunk buffer
+        let mut chunk = [0.0; ROW_CHUNK as usize];
 
         for i in bbox.y0..bbox.y1 {
-            let line_off = (i * self.width) as usize;
+            let line_off = i as usize * stride;
 
-            for j in bbox.x0..bbox.x1 {
-                let pix_off = line_off + j as usize;
+            for x0 in (bbox.x0..bbox.x1).step_by(ROW_CHUNK as usize) {
+                let x1 = bbox.x1.min(x0 + ROW_CHUNK);
+                let row = &mut chunk[..(x1 - x0) as usize];
 
-                let pixval = self.eval_spot_pixel(position, &shape_inv, intensity, j, i);
+                self.eval_row(pattern, x0, i, row);
 
-                // Compose light spot patterns using linear intesity addition
-                // with numeric saturation instead of wrapping overflow.
-                self.pixbuf[pix_off] = self.pixbuf[pix_off].saturating_add(pixval);
+                for (j, &pattern_val) in (x0..x1).zip(row.iter()) {
+                    let pix_off = line_off + j as usize * channels;
+
+                    match color_mode {
+                        ColorMode::Monochrome => add(pix_off, intensity * pattern_val),
+                        ColorMode::Bayer(mosaic) => {
+                            // Apply the color filter mosaic channel weight to the spot intensity.
+                            let weight = color.channel(mosaic.channel(j, i));
+                            add(pix_off, intensity * weight * pattern_val);
+                        }
+                        ColorMode::Rgb => {
+                            for c in 0..channels {
+                                let weight = color.channel(c);
+                                add(pix_off + c, intensity * weight * pattern_val);
+                            }
+                        }
+                    }
+                }
             }
         }
     }
 
-    /// Evaluates the spot pixel intensity as a function of the radius vector
+    /// Clips the light spot bounding box to the canvas region.
+    ///
+    /// Returns `None` if the clipped bounding box is empty.
+    #[must_use]
+    fn clip(&self, region: &BoundingBox) -> Option<SpotRaster> {
+        let bbox = self.bbox.intersect(region);
+
+        if bbox.is_empty() {
+            None
+        } else {
+            Some(SpotRaster { bbox, ..*self })
+        }
+    }
+
+    /// Evaluates the spot pattern intensity as a function of the radius vector
     /// drawn from the spot center.
     ///
     /// This version calculates a unit Airy disk pattern deformed
     /// by the `SpotShape` transformation matrix.
     #[must_use]
-    fn eval_spot_pixel(
-        &self,
-        center: Point,
-        shape_inv: &SpotShape,
-        intensity: f32,
-        x: u32,
-        y: u32,
-    ) -> Pixel {
+    fn eval_pattern(&self, pattern: &AiryPattern, x: u32, y: u32) -> f32 {
+        let center = self.position;
+
         // Current pixel radius vector
         let rvec = (((x as f32) - center.0), ((y as f32) - center.1));
 
         // Transformed radius vector components
-        let (tx, ty) = shape_inv.apply(rvec);
+        let (tx, ty) = self.shape_inv.apply(rvec);
 
         // Transformed radial distance
-        let rdist = tx.hypot(ty);
+        // (evaluated exactly as in the vectorized row evaluator)
+        let rdist = (tx * tx + ty * ty).sqrt();
 
         // Perform pre-computed spot pattern LUT lookup for each pixel.
-        let pattern_val = self.pattern.eval(rdist);
+        pattern.eval(rdist)
+    }
+}
 
-        // Calculate the final pixel value
-        (intensity * pattern_val * f32::from(Pixel::MAX)) as Pixel
+/// Adds the light spot pattern contribution to the pixel value.
+fn add_pixel<P: CanvasPixel>(pixel: P, value: f32) -> P {
+    // Compose light spot patterns using linear intesity addition
+    // with numeric saturation instead of wrapping overflow.
+    pixel.saturating_add(P::from_level(value * P::FULL_SCALE))
+}
+
+/// Adds the light spot sample value to the canvas sample buffers at `offset`.
+///
+/// The floating point sum buffer is empty if disabled. The pixel buffer
+/// is left intact if the floating point accumulation is enabled.
+fn add_sample<P: CanvasPixel>(
+    pixbuf: &mut [P],
+    hdrbuf: &mut [f32],
+    accumulate: bool,
+    offset: usize,
+    value: f32,
+) {
+    if let Some(v) = hdrbuf.get_mut(offset) {
+        *v += value;
+    }
+
+    if !accumulate {
+        pixbuf[offset] = add_pixel(pixbuf[offset], value);
+    }
+}
+
+/// Quantizes the floating point light spot sums into the pixel values.
+///
+/// The sums include the normalized background level, the light spot
+/// contributions are scaled by `full_scale` above the background.
+fn quantize_samples<P: CanvasPixel>(
+    pixels: &mut [P],
+    sums: &[f32],
+    background: P,
+    full_scale: f32,
+) {
+    let level = background.to_level();
+    let normalized = level / P::FULL_SCALE;
+
+    for (p, v) in pixels.iter_mut().zip(sums) {
+        *p = P::quantize((v - normalized) * full_scale + level);
+    }
+}
+
+/// Splits the floating point sample buffer into `bands` bands
+/// of `band_len` samples.
+///
+/// The disabled (empty) buffers are split into empty bands.
+#[cfg(feature = "rayon")]
+fn split_bands(buf: &mut [f32], band_len: usize, bands: usize) -> Vec<&mut [f32]> {
+    if buf.is_empty() {
+        (0..bands).map(|_| <&mut [f32]>::default()).collect()
+    } else {
+        buf.chunks_mut(band_len).collect()
+    }
+}
+
+impl<P: CanvasPixel> Canvas<P> {
+    /// Checks if the light spot bounding box intersects the canvas rectangle.
+    pub(crate) fn is_spot_visible(&self, spot_id: SpotId) -> bool {
+        self.spot_bbox(spot_id).is_some_and(|bbox| !bbox.is_empty())
+    }
+
+    /// Calculates the light spot bounding box clipped to the canvas dimensions.
+    fn spot_bbox(&self, spot_id: SpotId) -> Option<BoundingBox> {
+        let position = self.spot_position(spot_id)?;
+        let shape = self.spots[spot_id].shape;
+
+        Some(BoundingBox::new(position, &shape, self.width, self.height))
+    }
+
+    /// Checks if the light spot bounding box intersects the canvas region.
+    pub(super) fn is_spot_in_region(&self, spot_id: SpotId, region: &BoundingBox) -> bool {
+        self.spot_bbox(spot_id)
+            .is_some_and(|bbox| !bbox.intersect(region).is_empty())
+    }
+
+    /// Returns the light spots possibly intersecting the canvas region
+    /// in the draw list order.
+    ///
+    /// Culls the spots outside of the region using the spatial index
+    /// if enabled, otherwise returns all spots.
+    #[allow(clippy::cast_precision_loss)]
+    pub(super) fn region_spots(&self, region: &BoundingBox) -> Vec<SpotId> {
+        let Some(index) = &self.index else {
+            return (0..self.spots.len()).collect();
+        };
+
+        // Extend the region by the maximum spot radius
+        // and the bounding box rounding margin.
+        let (rx, ry) = index.max_radius();
+        let min = (region.x0 as f32 - rx - 1.0, region.y0 as f32 - ry - 1.0);
+        let max = (region.x1 as f32 + rx + 1.0, region.y1 as f32 + ry + 1.0);
+
+        match self.transform.inverse_rect(min, max) {
+            Some((min, max)) => index.query(min, max),
+            None => (0..self.spots.len()).collect(),
+        }
+    }
+
+    /// Marks the light spot bounding box as a dirty canvas region.
+    ///
+    /// Falls back to the full redraw if too many regions are dirty.
+    pub(super) fn mark_spot_dirty(&mut self, spot_id: SpotId) {
+        let Some(bbox) = self.spot_bbox(spot_id) else {
+            return;
+        };
+
+        let Some(regions) = &mut self.dirty else {
+            return;
+        };
+
+        if bbox.is_empty() || regions.contains(&bbox) {
+            return;
+        }
+
+        if regions.len() < MAX_DIRTY_REGIONS {
+            regions.push(bbox);
+        } else {
+            self.dirty = None;
+        }
+    }
+
+    /// Calculates the light spot rasterization parameters
+    /// for a `width` x `height` pixel frame.
+    ///
+    /// Returns `None` if the spot is dark or clipped out of the frame.
+    fn spot_raster(&self, spot_id: SpotId, width: u32, height: u32) -> Option<SpotRaster> {
+        let position = self.spot_position(spot_id)?;
+        let intensity = self.spot_intensity(spot_id)?;
+
+        let spot = &self.spots[spot_id];
+
+        // Fast path for dark spots
+        if intensity <= 0.0 {
+            return None;
+        }
+
+        let bbox = BoundingBox::new(position, &spot.shape, width, height);
+
+        // Check is the spot is clipped out of the frame.
+        if bbox.is_empty() {
+            return None;
+        }
+
+        Some(SpotRaster {
+            position,
+            shape_inv: spot.shape_inv,
+            intensity,
+            color: spot.color,
+            bbox,
+        })
+    }
+
+    /// Draws a single light spot image on the canvas.
+    #[cfg_attr(feature = "rayon", allow(dead_code))]
+    pub(super) fn draw_spot(&mut self, spot_id: SpotId) {
+        let Some(raster) = self.spot_raster(spot_id, self.width, self.height) else {
+            return;
+        };
+
+        self.draw_raster(&raster);
+    }
+
+    /// Draws the rasterized light spot image on the canvas.
+    fn draw_raster(&mut self, raster: &SpotRaster) {
+        let pixbuf = &mut self.pixbuf;
+        let hdrbuf = &mut self.hdrbuf;
+        let accumulate = self.full_scale.is_some();
+
+        raster.render(
+            &self.pattern,
+            self.color_mode,
+            self.stride,
+            |offset, value| {
+                add_sample(pixbuf, hdrbuf, accumulate, offset, value);
+            },
+        );
+    }
+
+    /// Redraws the canvas regions.
+    ///
+    /// Clears each region and draws the light spots clipped to it
+    /// in the draw list order, so the region pixels are identical
+    /// to the full canvas redraw. The light spots are rasterized once
+    /// for all regions.
+    pub(super) fn draw_regions(&mut self, regions: &[BoundingBox]) {
+        let Some(bounds) = regions.iter().copied().reduce(|a, b| a.union(&b)) else {
+            return;
+        };
+
+        let rasters: Vec<SpotRaster> = if self.brightness > 0.0 {
+            self.region_spots(&bounds)
+                .into_iter()
+                .filter_map(|spot_id| self.spot_raster(spot_id, self.width, self.height))
+                .filter_map(|raster| raster.clip(&bounds))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Number of interleaved color channel samples per pixel
+        let channels = self.color_mode.channels();
+        let hdr_background = self.background.to_level() / P::FULL_SCALE;
+
+        for region in regions {
+            let (x0, x1) = (region.x0 as usize * channels, region.x1 as usize * channels);
+
+            for i in region.y0..region.y1 {
+                let span = i as usize * self.stride + x0..i as usize * self.stride + x1;
+
+                self.pixbuf[span.clone()].fill(self.background);
+
+                // The floating point buffer is empty if disabled.
+                if let Some(hdr) = self.hdrbuf.get_mut(span) {
+                    hdr.fill(hdr_background);
+                }
+            }
+
+            for raster in rasters.iter().filter_map(|r| r.clip(region)) {
+                self.draw_raster(&raster);
+            }
+
+            let Some(full_scale) = self.full_scale else {
+                continue;
+            };
+
+            for i in region.y0..region.y1 {
+                let span = i as usize * self.stride + x0..i as usize * self.stride + x1;
+
+                quantize_samples(
+                    &mut self.pixbuf[span.clone()],
+                    &self.hdrbuf[span],
+                    self.background,
+                    full_scale,
+                );
+            }
+        }
+    }
+
+    /// Draws all light spots on the canvas in parallel.
+    ///
+    /// The canvas image is split into horizontal bands rendered
+    /// by the rayon thread pool. The light spots are binned into the bands
+    /// by their bounding boxes, so each band is rendered independently.
+    /// The spots are drawn in the draw list order within each band,
+    /// so the result is identical to drawing the spots one by one.
+    #[cfg(feature = "rayon")]
+    pub(super) fn draw_spots_parallel(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let stride = self.stride;
+
+        if self.pixbuf.is_empty() {
+            return;
+        }
+
+        let rasters: Vec<SpotRaster> = self
+            .region_spots(&Window::new(width, height).into())
+            .into_par_iter()
+            .filter_map(|spot_id| self.spot_raster(spot_id, width, height))
+            .collect();
+
+        // Bin the light spots into the bands by the bounding box rows.
+        // Bands are identified by the first row index.
+        let mut bins: Vec<(u32, Vec<&SpotRaster>)> = (0..height)
+            .step_by(BAND_HEIGHT as usize)
+            .map(|y0| (y0, Vec::new()))
+            .collect();
+
+        for raster in &rasters {
+            let first = (raster.bbox.y0 / BAND_HEIGHT) as usize;
+            let last = ((raster.bbox.y1 - 1) / BAND_HEIGHT) as usize;
+
+            for (_, bin) in &mut bins[first..=last] {
+                bin.push(raster);
+            }
+        }
+
+        let band_len = BAND_HEIGHT as usize * stride;
+
+        let hdr_bands = split_bands(&mut self.hdrbuf, band_len, bins.len());
+
+        let (pattern, color_mode) = (&self.pattern, self.color_mode);
+        let accumulate = self.full_scale.is_some();
+
+        self.pixbuf
+            .par_chunks_mut(band_len)
+            .zip(hdr_bands)
+            .zip(bins)
+            .for_each(|((pixbuf, hdrbuf), (y0, bin))| {
+                let base = y0 as usize * stride;
+
+                let band = BoundingBox {
+                    x0: 0,
+                    y0,
+                    x1: width,
+                    y1: y0 + BAND_HEIGHT,
+                };
+
+                for raster in bin {
+                    let Some(raster) = raster.clip(&band) else {
+                        continue;
+                    };
+
+                    raster.render(pattern, color_mode, stride, |offset, value| {
+                        add_sample(pixbuf, hdrbuf, accumulate, offset - base, value);
+                    });
+                }
+            });
+    }
+
+    /// Draws the light spots into an external `width` x `height`
+    /// pixel frame buffer with `stride` samples per row.
+    ///
+    /// The light spot samples are accumulated in the floating point scratch
+    /// buffer and quantized afterwards if the floating point accumulation
+    /// is enabled, so the result matches the canvas pixel buffer one.
+    ///
+    /// The frame buffer dimensions are validated by the caller
+    /// and the frame rows are expected to be filled with the background.
+    pub(super) fn draw_spots_into(
+        &mut self,
+        buf: &mut [P],
+        (width, height): (u32, u32),
+        stride: usize,
+    ) {
+        let spots = self.region_spots(&Window::new(width, height).into());
+
+        let Some(full_scale) = self.full_scale else {
+            for raster in spots
+                .into_iter()
+                .filter_map(|spot_id| self.spot_raster(spot_id, width, height))
+            {
+                raster.render(&self.pattern, self.color_mode, stride, |offset, value| {
+                    buf[offset] = add_pixel(buf[offset], value);
+                });
+            }
+
+            return;
+        };
+
+        let row_len = width as usize * self.color_mode.channels();
+
+        // The last row may be shorter than the stride.
+        let len = (height as usize).saturating_sub(1) * stride + row_len;
+
+        // Reuse the scratch buffer allocation.
+        let mut sums = std::mem::take(&mut self.scratch);
+        sums.clear();
+        sums.resize(len, self.background.to_level() / P::FULL_SCALE);
+
+        for raster in spots
+            .into_iter()
+            .filter_map(|spot_id| self.spot_raster(spot_id, width, height))
+        {
+            raster.render(&self.pattern, self.color_mode, stride, |offset, value| {
+                sums[offset] += value;
+            });
+        }
+
+        for (row, sums) in buf.chunks_mut(stride).zip(sums.chunks(stride)) {
+            quantize_samples(&mut row[..row_len], sums, self.background, full_scale);
+        }
+
+        self.scratch = sums;
+    }
+
+    /// Quantizes the accumulated light spot samples into the pixel buffer
+    /// if the floating point accumulation is enabled.
+    pub(super) fn quantize_accumulated(&mut self) {
+        if let Some(full_scale) = self.full_scale {
+            quantize_samples(&mut self.pixbuf, &self.hdrbuf, self.background, full_scale);
+        }
     }
 }
 
@@ -309,4 +770,285 @@ mod tests {
         c.draw_spot(spot4);
         assert_eq!(c.pixbuf[8 * 5 + 5], 6755);
     }
+
+    #[test]
+    fn render_wide_spot() {
+        let mut c = Canvas::new(200, 100);
+        let spot = c.add_spot((97.3, 50.6), SpotShape::default().scale(30.0), 0.5);
+
+        // The spot rows span several row evaluation chunks.
+        let raster = c.spot_raster(spot, 200, 100).unwrap();
+        assert!(raster.bbox.x1 - raster.bbox.x0 > ROW_CHUNK + 4);
+
+        let mut samples = vec![0.0; 200 * 100];
+        raster.render(&c.pattern, c.color_mode, 200, |offset, value| {
+            samples[offset] += value;
+        });
+
+        let bbox = &raster.bbox;
+
+        for i in bbox.y0..bbox.y1 {
+            for j in bbox.x0..bbox.x1 {
+                let v = samples[i as usize * 200 + j as usize];
+                assert_eq!(v, 0.5 * raster.eval_pattern(&c.pattern, j, i));
+            }
+        }
+    }
+
+    #[test]
+    fn draw_accumulated_spots() {
+        let shape = SpotShape::default().scale(2.0);
+        let mut c = Canvas::new(8, 8);
+        c.set_background(100);
+
+        // Many faint overlapping spots are truncated to zero one by one.
+        for _ in 0..1000 {
+            c.add_spot((3.0, 3.0), shape, 1e-5);
+        }
+
+        c.draw();
+        assert!(c.pixbuf.iter().all(|&p| p == 100));
+
+        c.set_float_accumulation(Some(65535.0));
+        assert_eq!(c.float_accumulation(), Some(65535.0));
+
+        c.draw();
+        let peak = c.pixbuf[8 * 3 + 3];
+        assert!(peak.abs_diff(100 + 655) <= 2, "peak = {peak}");
+
+        // The HDR rendering shares the floating point sum buffer.
+        let pixels = c.pixbuf.clone();
+        c.set_hdr_rendering(true);
+        c.draw();
+        assert_eq!(c.pixbuf, pixels);
+        assert_eq!(c.hdr_pixels().map(<[f32]>::len), Some(64));
+
+        c.set_hdr_rendering(false);
+        assert!(c.hdr_pixels().is_none());
+        assert_eq!(c.hdrbuf.len(), 64);
+        c.draw();
+        assert_eq!(c.pixbuf, pixels);
+
+        // Scale the unit intensity to the 12-bit pixel range.
+        let mut c = Canvas::new(8, 8);
+        c.set_float_accumulation(Some(4095.0));
+
+        let spot = c.add_spot((3.0, 3.0), shape, 1.0);
+        c.draw();
+        assert_eq!(c.pixbuf[8 * 3 + 3], 4095);
+
+        // The quantized samples saturate at the maximum pixel value.
+        c.set_spot_illumination(spot, 100.0);
+        c.draw();
+        assert_eq!(c.pixbuf[8 * 3 + 3], u16::MAX);
+
+        c.set_float_accumulation(None);
+        assert!(c.float_accumulation().is_none());
+        assert!(c.hdrbuf.is_empty());
+    }
+
+    #[test]
+    fn draw_generic_pixels() {
+        let shape = SpotShape::default().scale(2.0);
+
+        let mut c16 = Canvas::new(8, 8);
+        let mut c8 = Canvas::<u8>::with_dimensions(8, 8);
+        let mut c32 = Canvas::<u32>::with_dimensions(8, 8);
+        let mut cf = Canvas::<f32>::with_dimensions(8, 8);
+
+        c16.add_spot((3.5, 3.5), shape, 0.8);
+        c8.add_spot((3.5, 3.5), shape, 0.8);
+        c32.add_spot((3.5, 3.5), shape, 0.8);
+        cf.add_spot((3.5, 3.5), shape, 0.8);
+
+        c16.draw();
+        c8.draw();
+        c32.draw();
+        cf.draw();
+
+        for (i, &p) in c16.pixels().iter().enumerate() {
+            assert!(p.abs_diff(c8.pixels()[i].to_u16()) <= 0x101);
+            assert!(p.abs_diff(c32.pixels()[i].to_u16()) <= 1);
+            assert!(p.abs_diff(cf.pixels()[i].to_u16()) <= 1);
+        }
+
+        // The floating point pixels do not saturate.
+        cf.set_brightness(10.0);
+        cf.draw();
+        assert!(cf.pixels()[8 * 3 + 3] > 1.0);
+        assert_eq!(cf.pixels()[8 * 3 + 3].to_u16(), u16::MAX);
+    }
+
+    /// Draws the light spots one by one as `draw()` does without rayon.
+    #[cfg(feature = "rayon")]
+    fn draw_serial<P: CanvasPixel>(c: &mut Canvas<P>) {
+        c.clear();
+
+        for spot_id in 0..c.spots.len() {
+            c.draw_spot(spot_id);
+        }
+
+        c.quantize_accumulated();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn draw_parallel_bands() {
+        let mut c = Canvas::new(150, 100);
+        c.set_background(1000);
+
+        // Overlapping spots crossing the band and canvas boundaries
+        let mut seed = 12345u32;
+        let mut rand = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+
+        for _ in 0..300 {
+            let position = (rand() * 170.0 - 10.0, rand() * 120.0 - 10.0);
+            let shape = SpotShape::default().scale(1.0 + 5.0 * rand());
+            c.add_spot(position, shape, 0.05 + rand());
+        }
+
+        let check = |c: &mut Canvas| {
+            c.draw();
+            let pixels = c.pixels().to_vec();
+            let hdr = c.hdr_pixels().map(<[f32]>::to_vec);
+
+            draw_serial(c);
+            assert_eq!(pixels, c.pixels());
+            assert_eq!(hdr.as_deref(), c.hdr_pixels());
+        };
+
+        check(&mut c);
+
+        c.set_row_stride(160).unwrap();
+        c.set_hdr_rendering(true);
+        check(&mut c);
+
+        c.set_float_accumulation(Some(30000.0));
+        check(&mut c);
+
+        c.set_color_mode(ColorMode::Rgb);
+        check(&mut c);
+
+        c.set_color_mode(ColorMode::Bayer(crate::BayerPattern::Gbrg));
+        c.set_float_accumulation(None);
+        check(&mut c);
+    }
+
+    #[test]
+    fn redraw_dirty_regions() {
+        let mut c = Canvas::new(120, 90);
+        c.set_background(500);
+        c.set_row_stride(128).unwrap();
+
+        let shape = SpotShape::default().scale(3.0);
+        let spots: Vec<_> = (0..20)
+            .map(|i| {
+                let position = (6.3 * i as f32, 4.1 * i as f32 + 3.0);
+                c.add_spot(position, shape.rotate(i as f32), 0.1 + 0.05 * i as f32)
+            })
+            .collect();
+
+        // Redrawing the undrawn canvas falls back to the full redraw.
+        assert!(c.dirty.is_none());
+        c.redraw();
+        assert_eq!(c.dirty, Some(Vec::new()));
+
+        let check = |c: &mut Canvas| {
+            c.redraw();
+            let pixels = c.pixels().to_vec();
+            let hdr = c.hdr_pixels().map(<[f32]>::to_vec);
+
+            c.draw();
+            assert_eq!(pixels, c.pixels());
+            assert_eq!(hdr.as_deref(), c.hdr_pixels());
+        };
+
+        // Overlapping spot moves and the canvas edge crossings
+        c.set_spot_offset(spots[3], (2.7, -1.2));
+        c.set_spot_offset(spots[4], (-30.0, 0.5));
+        assert_eq!(c.dirty.as_ref().unwrap().len(), 4);
+
+        c.set_spot_illumination(spots[10], 2.5);
+        c.add_spot((60.2, 44.8), shape.scale(2.0), 0.9);
+        check(&mut c);
+
+        c.set_hdr_rendering(true);
+        c.set_float_accumulation(Some(40000.0));
+        assert!(c.dirty.is_none());
+        check(&mut c);
+
+        c.set_spot_offset(spots[5], (1.5, 1.5));
+        c.set_spot_illumination(spots[6], 0.0);
+        check(&mut c);
+
+        // Too many dirty regions fall back to the full redraw.
+        for i in 0..80 {
+            c.set_spot_offset(spots[i % 20], ((i / 20) as f32 * 1.5, 0.0));
+        }
+
+        assert!(c.dirty.is_none());
+        check(&mut c);
+
+        // Spots outside of the canvas do not mark any regions.
+        c.set_spot_offset(spots[0], (-50.0, -50.0));
+        c.set_spot_offset(spots[0], (-40.0, -50.0));
+        assert_eq!(c.dirty.as_ref().unwrap().len(), 1);
+        check(&mut c);
+    }
+
+    #[test]
+    fn draw_bayer_spot() {
+        let shape = SpotShape::default().scale(2.0);
+        let mut c = Canvas::new(8, 8);
+
+        let spot = c.add_spot((3.5, 3.5), shape, 0.8);
+        c.draw_spot(spot);
+
+        let mono = c.pixbuf.clone();
+
+        c.clear();
+        c.set_color_mode(ColorMode::Bayer(crate::BayerPattern::Rggb));
+        c.set_spot_color(spot, SpotColor::new(1.0, 0.5, 0.0));
+        c.draw_spot(spot);
+
+        // Red pixels
+        assert_eq!(c.pixbuf[8 * 2 + 2], mono[8 * 2 + 2]);
+        assert_eq!(c.pixbuf[8 * 4 + 4], mono[8 * 4 + 4]);
+
+        // Green pixels
+        assert_eq!(c.pixbuf[8 * 2 + 3], 1233);
+        assert_eq!(c.pixbuf[8 * 3 + 2], 1233);
+        assert!(mono[8 * 2 + 3].abs_diff(2 * c.pixbuf[8 * 2 + 3]) <= 1);
+
+        // Blue pixels
+        assert_eq!(c.pixbuf[8 * 3 + 3], 0);
+        assert_ne!(mono[8 * 3 + 3], 0);
+    }
+
+    #[test]
+    fn draw_rgb_spot() {
+        let shape = SpotShape::default().scale(2.0);
+        let mut c = Canvas::new(8, 8);
+
+        let spot = c.add_spot((3.5, 3.5), shape, 0.8);
+        c.draw_spot(spot);
+
+        let mono = c.pixbuf.clone();
+
+        c.set_color_mode(ColorMode::Rgb);
+        c.set_spot_color(spot, SpotColor::new(1.0, 0.5, 0.0));
+        c.clear();
+        c.draw_spot(spot);
+
+        assert_eq!(c.pixbuf.len(), 3 * mono.len());
+
+        for (rgb, gray) in c.pixbuf.chunks(3).zip(mono) {
+            assert_eq!(rgb[0], gray);
+            assert!(gray.abs_diff(2 * rgb[1]) <= 1);
+            assert_eq!(rgb[2], 0);
+        }
+    }
 }
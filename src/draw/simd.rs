@@ -0,0 +1,165 @@
+//! Planetarium
+//! ===========
+//!
+//! Private vectorized light spot pattern evaluation routines
+//! ---------------------------------------------------------
+//!
+//! Contains the SSE2 accelerated pattern row evaluator for `x86_64`
+//! and the portable scalar fallback implementation.
+
+use super::SpotRaster;
+use crate::pattern::AiryPattern;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    _mm_add_ps, _mm_mul_ps, _mm_set1_ps, _mm_setr_ps, _mm_sqrt_ps, _mm_storeu_ps, _mm_sub_ps,
+};
+
+/// Number of pixels evaluated per vectorized iteration
+#[cfg(target_arch = "x86_64")]
+const LANES: usize = 4;
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+impl SpotRaster {
+    /// Evaluates the spot pattern intensity for a row of pixels
+    /// starting at `(x0, y)` into `out`.
+    ///
+    /// The results are identical to the per-pixel `eval_pattern()` ones.
+    pub(super) fn eval_row(&self, pattern: &AiryPattern, x0: u32, y: u32, out: &mut [f32]) {
+        #[cfg(target_arch = "x86_64")]
+        self.eval_row_sse2(pattern, x0, y, out);
+
+        #[cfg(not(target_arch = "x86_64"))]
+        self.eval_row_scalar(pattern, x0, y, out);
+    }
+
+    /// Evaluates the spot pattern intensity for a row of pixels
+    /// one pixel at a time.
+    pub(super) fn eval_row_scalar(&self, pattern: &AiryPattern, x0: u32, y: u32, out: &mut [f32]) {
+        for (x, v) in (x0..).zip(out.iter_mut()) {
+            *v = self.eval_pattern(pattern, x, y);
+        }
+    }
+
+    /// Evaluates the spot pattern intensity for a row of pixels
+    /// using the SSE2 instructions for the radial distance calculation
+    /// and the pattern LUT lookups.
+    #[cfg(target_arch = "x86_64")]
+    pub(super) fn eval_row_sse2(&self, pattern: &AiryPattern, x0: u32, y: u32, out: &mut [f32]) {
+        let center = self.position;
+        let shape = &self.shape_inv;
+
+        let mut chunks = out.chunks_exact_mut(LANES);
+        let mut x = x0;
+
+        // SAFETY: SSE2 is always available on x86_64.
+        unsafe {
+            // Radius vector Y component is the same for the whole row.
+            let ry = _mm_set1_ps((y as f32) - center.1);
+            let cx = _mm_set1_ps(center.0);
+
+            let (xx, xy) = (_mm_set1_ps(shape.xx), _mm_set1_ps(shape.xy));
+            let (yx, yy) = (_mm_set1_ps(shape.yx), _mm_set1_ps(shape.yy));
+
+            for chunk in &mut chunks {
+                let px = _mm_setr_ps(x as f32, (x + 1) as f32, (x + 2) as f32, (x + 3) as f32);
+
+                // Current pixel radius vectors
+                let rx = _mm_sub_ps(px, cx);
+
+                // Transformed radius vector components
+                // in the same operation order as `SpotShape::apply()`
+                let tx = _mm_add_ps(_mm_mul_ps(rx, xx), _mm_mul_ps(ry, xy));
+                let ty = _mm_add_ps(_mm_mul_ps(ry, yy), _mm_mul_ps(rx, yx));
+
+                // Transformed radial distances
+                let rdist = _mm_sqrt_ps(_mm_add_ps(_mm_mul_ps(tx, tx), _mm_mul_ps(ty, ty)));
+
+                _mm_storeu_ps(chunk.as_mut_ptr(), pattern.eval_sse2(rdist));
+
+                x += LANES as u32;
+            }
+        }
+
+        self.eval_row_scalar(pattern, x, y, chunks.into_remainder());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::BoundingBox;
+    use crate::{SpotColor, SpotShape};
+
+    /// Creates a light spot raster for the shape at the position.
+    fn mkraster(position: (f32, f32), shape: SpotShape) -> SpotRaster {
+        SpotRaster {
+            position,
+            shape_inv: shape.invert(),
+            intensity: 1.0,
+            color: SpotColor::default(),
+            bbox: BoundingBox::new(position, &shape, 64, 64),
+        }
+    }
+
+    #[test]
+    fn eval_rows() {
+        use crate::PatternInterpolation::{Linear, Nearest};
+
+        for pattern in [
+            AiryPattern::new(),
+            AiryPattern::with_params(100, Nearest),
+            AiryPattern::with_params(4096, Linear),
+        ] {
+            eval_pattern_rows(&pattern);
+        }
+    }
+
+    /// Checks the vectorized row evaluation results of the pattern LUT.
+    fn eval_pattern_rows(pattern: &AiryPattern) {
+        let shapes = [
+            SpotShape::default(),
+            SpotShape::default().scale(4.5),
+            SpotShape::default().stretch(3.0, 1.5).rotate(30.0),
+            SpotShape::default().scale(7.3).rotate(-71.0),
+        ];
+
+        for shape in shapes {
+            for position in [(20.0, 30.0), (31.27, 12.9), (-2.5, 60.1)] {
+                let raster = mkraster(position, shape);
+
+                // Odd row lengths exercise the scalar tail.
+                let mut row = [0.0; 45];
+                let mut scalar = [0.0; 45];
+
+                for y in 0..64 {
+                    raster.eval_row(pattern, 3, y, &mut row);
+                    raster.eval_row_scalar(pattern, 3, y, &mut scalar);
+
+                    assert_eq!(row, scalar);
+
+                    for (x, v) in (3..).zip(row) {
+                        assert_eq!(v, raster.eval_pattern(pattern, x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn eval_rows_sse2() {
+        let pattern = AiryPattern::new();
+        let raster = mkraster((10.6, 8.1), SpotShape::default().scale(3.0).rotate(15.0));
+
+        for len in 0..12 {
+            let mut row = vec![-1.0; len];
+            let mut scalar = vec![-1.0; len];
+
+            raster.eval_row_sse2(&pattern, 2, 9, &mut row);
+            raster.eval_row_scalar(&pattern, 2, 9, &mut scalar);
+
+            assert_eq!(row, scalar);
+        }
+    }
+}
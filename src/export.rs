@@ -9,12 +9,29 @@
 //!
 //! Defines a custom error enum type `EncoderError`.
 
+mod npy;
 mod raw;
+mod ser;
+mod tiff;
+mod y4m;
 
 #[cfg(feature = "png")]
 mod png;
 
-use crate::{Canvas, Pixel};
+#[cfg(feature = "png")]
+mod apng;
+
+use std::io::Write;
+
+use crate::{BayerPattern, Canvas, CanvasPixel, ColorMode, Pixel};
+
+use self::npy::NpyDtype;
+
+pub use self::ser::SerWriter;
+pub use self::y4m::{Y4mChroma, Y4mWriter};
+
+#[cfg(feature = "png")]
+pub use self::apng::ApngWriter;
 
 /// Canvas image window coordinates
 ///
@@ -73,12 +90,24 @@ pub enum ImageFormat {
     RawLinear10BppLE,
     /// 12-bit linear light grayscale little-endian RAW
     RawLinear12BppLE,
+    /// 16-bit linear light grayscale little-endian RAW
+    RawLinear16BppLE,
+    /// 16-bit linear light grayscale NumPy array (`uint16`)
+    NpyLinear16Bpp,
+    /// Normalized linear light grayscale NumPy array (`float32`)
+    NpyLinearFloat,
+    /// 32-bit floating point linear light grayscale little-endian TIFF
+    TiffLinearFloat,
 
     // Require "png" feature:
     /// 8-bit gamma-compressed grayscale PNG
     PngGamma8Bpp,
     /// 16-bit linear light grayscale PNG
     PngLinear16Bpp,
+    /// 8-bit gamma-compressed RGB color PNG
+    PngRgbGamma8Bpp,
+    /// 16-bit linear light RGB color PNG
+    PngRgbLinear16Bpp,
 }
 
 /// Image export encoder error type
@@ -91,11 +120,117 @@ pub enum EncoderError {
     BrokenWindow,
     /// Requested image subsampling factors are too large or zero
     InvalidSubsamplingRate,
+    /// Requested image format is incompatible with the canvas color mode
+    IncompatibleColorMode,
+    /// Writing the encoded image data failed with an I/O error
+    IoError(std::io::ErrorKind),
+    /// Caller-provided output buffer is too small for the encoded image
+    BufferTooSmall,
+    /// Requested image windows overlap each other
+    OverlappingWindows,
+    /// Requested encoder parameters are invalid
+    InvalidParameters,
+    /// Requested image format is not supported by the encoder method
+    UnsupportedFormat,
+    /// Requested image is too large for the image format
+    ImageTooLarge,
+}
+
+/// Subsampled image pixel binning modes
+///
+/// Pixel binning combines all canvas pixels in each subsampling block
+/// into a single subsampled image pixel instead of picking one of them.
+///
+/// In the Bayer color mode only the pixels of the same color channel
+/// are binned together, so the subsampled image retains the mosaic
+/// and the Bayer CFA pattern of the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binning {
+    /// Sums the binned pixel values with saturation
+    Sum,
+    /// Averages the binned pixel values with rounding
+    Average,
+}
+
+/// Canvas image export options
+///
+/// The export options are set with `Canvas::set_export_options()`
+/// and apply to all subsequent image exports from the canvas.
+///
+/// Usage
+/// -----
+///
+/// ```
+/// use planetarium::{Canvas, ExportOptions, PngCompression, PngFilter};
+///
+/// let mut c = Canvas::new(256, 256);
+///
+/// // Embed the rendering parameters into the exported PNG images.
+/// let mut options = ExportOptions::default();
+/// options.metadata = true;
+///
+/// // Make the exported PNG images smaller.
+/// options.png_compression = PngCompression::Best;
+/// options.png_filter = PngFilter::Paeth;
+///
+/// c.set_export_options(options);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ExportOptions {
+    /// Stores the canvas rendering parameters and the light spot list
+    /// in the PNG image text chunks.
+    pub metadata: bool,
+    /// PNG image compression level
+    pub png_compression: PngCompression,
+    /// PNG image scanline filter type
+    pub png_filter: PngFilter,
+}
+
+/// PNG image compression levels
+///
+/// Trades the PNG encoding speed for the encoded image size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PngCompression {
+    /// Fast minimal compression (default)
+    #[default]
+    Fast,
+    /// Balanced compression speed and ratio
+    Balanced,
+    /// Slow high compression
+    Best,
+}
+
+/// PNG image scanline filter types
+///
+/// The scanline filters transform the image sample data
+/// to make it better compressible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PngFilter {
+    /// No filtering
+    None,
+    /// Difference to the previous pixel (default)
+    #[default]
+    Sub,
+    /// Difference to the pixel above
+    Up,
+    /// Difference to the average of the previous pixel and the pixel above
+    Average,
+    /// Difference to the Paeth predictor
+    Paeth,
+    /// Filter type selected for each scanline heuristically
+    Adaptive,
 }
 
 /// Canvas window image scanlines iterator
 ///
-/// Yields the window image pixel spans as `&[Pixel]` slices.
+/// Yields the window image pixel spans as `&[P]` slices of the canvas
+/// pixel type `P`.
+///
+/// In the RGB color mode the pixel spans contain interleaved
+/// R, G, B color channel samples.
 ///
 /// Usage
 /// -----
@@ -114,9 +249,9 @@ pub enum EncoderError {
 ///     assert_eq!(span, [0u16; 10]);
 /// }
 /// ```
-pub struct WindowSpans<'a> {
+pub struct WindowSpans<'a, P: CanvasPixel = Pixel> {
     /// Source canvas object
-    canvas: &'a Canvas,
+    canvas: &'a Canvas<P>,
 
     /// Canvas window rectangle
     window: Window,
@@ -125,21 +260,24 @@ pub struct WindowSpans<'a> {
     scanline: u32,
 }
 
-impl<'a> Iterator for WindowSpans<'a> {
+impl<'a, P: CanvasPixel> Iterator for WindowSpans<'a, P> {
     /// Image pixel span type
-    type Item = &'a [Pixel];
+    type Item = &'a [P];
 
     /// Iterates over the window image scanlines and returns the resulting
-    /// image pixel spans as `&'a [Pixel]`.
+    /// image pixel spans as `&'a [P]`.
     fn next(&mut self) -> Option<Self::Item> {
         // Terminate when the current scanline is outside of the window rectangle.
         if self.scanline >= self.window.y + self.window.h {
             return None;
         }
 
+        // Number of interleaved color channel samples per pixel
+        let channels = self.canvas.color_mode.channels();
+
         // Calculate the current pixel span indexes.
-        let base = (self.canvas.width * self.scanline + self.window.x) as usize;
-        let end = base + self.window.w as usize;
+        let base = self.canvas.stride * self.scanline as usize + self.window.x as usize * channels;
+        let end = base + self.window.w as usize * channels;
 
         self.scanline += 1;
 
@@ -153,7 +291,7 @@ impl<'a> Iterator for WindowSpans<'a> {
     }
 }
 
-impl<'a> ExactSizeIterator for WindowSpans<'a> {}
+impl<P: CanvasPixel> ExactSizeIterator for WindowSpans<'_, P> {}
 
 impl From<((u32, u32), (u32, u32))> for Window {
     /// Creates a window from a tuple `((x, y), (w, h))`.
@@ -179,6 +317,12 @@ impl std::fmt::Display for EncoderError {
 
 impl std::error::Error for EncoderError {}
 
+impl From<std::io::Error> for EncoderError {
+    fn from(err: std::io::Error) -> Self {
+        EncoderError::IoError(err.kind())
+    }
+}
+
 impl Window {
     /// Creates a new window with given dimensions located at the origin.
     #[must_use]
@@ -202,10 +346,21 @@ impl Window {
 
     /// Checks if the window rectangle is inside the canvas rectangle.
     #[must_use]
-    fn is_inside(&self, width: u32, height: u32) -> bool {
+    pub(crate) fn is_inside(&self, width: u32, height: u32) -> bool {
         self.x + self.w <= width && self.y + self.h <= height
     }
 
+    /// Checks if the window rectangle intersects the other window rectangle.
+    ///
+    /// Empty windows do not intersect any other windows.
+    #[must_use]
+    fn intersects(&self, other: &Window) -> bool {
+        self.x < other.x + other.w
+            && other.x < self.x + self.w
+            && self.y < other.y + other.h
+            && other.y < self.y + self.h
+    }
+
     /// Returns the total number of pixels in the window.
     #[must_use]
     fn len(&self) -> usize {
@@ -213,6 +368,112 @@ impl Window {
     }
 }
 
+impl ImageFormat {
+    /// Returns the number of color channels per pixel.
+    #[must_use]
+    fn channels(self) -> usize {
+        match self {
+            ImageFormat::PngRgbGamma8Bpp | ImageFormat::PngRgbLinear16Bpp => 3,
+            _ => 1,
+        }
+    }
+
+    /// Calculates the encoded RAW image size in bytes for `pixels` image pixels.
+    ///
+    /// Returns `None` for non-RAW image formats.
+    #[must_use]
+    fn raw_image_size(self, pixels: usize) -> Option<usize> {
+        match self {
+            ImageFormat::RawGamma8Bpp => Some(pixels),
+            ImageFormat::RawLinear10BppLE
+            | ImageFormat::RawLinear12BppLE
+            | ImageFormat::RawLinear16BppLE => Some(2 * pixels),
+            _ => None,
+        }
+    }
+
+    /// Estimates the encoded image buffer capacity for `pixels` image samples.
+    #[must_use]
+    fn buffer_capacity(self, pixels: usize) -> usize {
+        match self {
+            ImageFormat::RawGamma8Bpp => pixels,
+            ImageFormat::RawLinear10BppLE
+            | ImageFormat::RawLinear12BppLE
+            | ImageFormat::RawLinear16BppLE => 2 * pixels,
+            ImageFormat::NpyLinear16Bpp => NPY_HEADER_CAPACITY + 2 * pixels,
+            ImageFormat::NpyLinearFloat => NPY_HEADER_CAPACITY + 4 * pixels,
+            ImageFormat::TiffLinearFloat => TIFF_HEADER_CAPACITY + 4 * pixels,
+            _ => PNG_BUF_CAPACITY,
+        }
+    }
+}
+
+/// Estimated NumPy array file header size
+const NPY_HEADER_CAPACITY: usize = 128;
+
+/// Estimated TIFF image file header size
+const TIFF_HEADER_CAPACITY: usize = 256;
+
+/// Initial encoded PNG buffer capacity
+const PNG_BUF_CAPACITY: usize = 0x10000;
+
+/// Sample buffer size for the streaming image encoders
+const SAMPLE_BUF_SIZE: usize = 0x1000;
+
+/// Fixed-size buffered encoded sample data writer
+///
+/// Collects the encoded sample bytes in a stack buffer to avoid
+/// issuing small writes to the underlying writer.
+struct SampleWriter<W: Write> {
+    /// Underlying writer
+    writer: W,
+
+    /// Sample data buffer
+    buf: [u8; SAMPLE_BUF_SIZE],
+
+    /// Sample data buffer fill level
+    len: usize,
+}
+
+impl<W: Write> SampleWriter<W> {
+    /// Creates a new buffered sample writer.
+    #[must_use]
+    fn new(writer: W) -> Self {
+        SampleWriter {
+            writer,
+            buf: [0; SAMPLE_BUF_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Appends the encoded sample bytes to the buffer.
+    ///
+    /// Flushes the buffer to the underlying writer when it is full.
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.len + bytes.len() > SAMPLE_BUF_SIZE {
+            self.flush()?;
+        }
+
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+
+        Ok(())
+    }
+
+    /// Writes the buffered sample bytes to the underlying writer.
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.write_all(&self.buf[..self.len])?;
+        self.len = 0;
+
+        Ok(())
+    }
+
+    /// Flushes the buffer and releases the underlying writer.
+    fn finish(mut self) -> std::io::Result<()> {
+        self.flush()
+    }
+}
+
 /// Maximum supported image subsampling factor value
 const MAX_SUBSAMPLING_RATE: u32 = 16;
 
@@ -232,18 +493,114 @@ fn validate_subsampling_rate(factors: (u32, u32)) -> Result<(), EncoderError> {
     }
 }
 
-impl Canvas {
+impl<P: CanvasPixel> Canvas<P> {
+    /// Validates the multiple window image export parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if any window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::OverlappingWindows`] if any two window
+    /// rectangles overlap.
+    fn validate_windows(&self, windows: &[Window]) -> Result<(), EncoderError> {
+        if !windows.iter().all(|w| w.is_inside(self.width, self.height)) {
+            return Err(EncoderError::BrokenWindow);
+        }
+
+        for (i, wnd) in windows.iter().enumerate() {
+            if windows[i + 1..].iter().any(|other| wnd.intersects(other)) {
+                return Err(EncoderError::OverlappingWindows);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the subsampled window image parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero, or the window dimensions are not
+    /// divisible by the subsampling factors.
+    // `u32::is_multiple_of()` requires a newer Rust toolchain.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn validate_subsampled_window(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+    ) -> Result<(), EncoderError> {
+        if !window.is_inside(self.width, self.height) {
+            return Err(EncoderError::BrokenWindow);
+        }
+
+        validate_subsampling_rate(factors)?;
+
+        if window.w % factors.0 == 0 && window.h % factors.1 == 0 {
+            Ok(())
+        } else {
+            Err(EncoderError::InvalidSubsamplingRate)
+        }
+    }
+
+    /// Validates the image format compatibility with the canvas color mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the image format
+    /// has a different number of color channels than the canvas pixels.
+    fn validate_color_mode(&self, format: ImageFormat) -> Result<(), EncoderError> {
+        if format.channels() == self.color_mode.channels() {
+            Ok(())
+        } else {
+            Err(EncoderError::IncompatibleColorMode)
+        }
+    }
+
+    /// Returns the Bayer CFA pattern identifier of the exported image mosaic.
+    ///
+    /// The exported image origin is located at the `window` origin and
+    /// the image is subsampled with the integer subsampling `factors`.
+    /// Use `(1, 1)` subsampling factors for the window image exports.
+    ///
+    /// The RAW image formats do not carry any metadata, so the pattern
+    /// identifier of a RAW image must be obtained by calling this method.
+    /// The PNG image formats store the pattern identifier in a text chunk.
+    ///
+    /// Returns `None` if the canvas is not mosaicked or the subsampled
+    /// image is not a valid Bayer mosaic (even subsampling factors).
+    ///
+    /// The binned images retain the canvas Bayer mosaic, so the binned image
+    /// pattern is obtained with `(1, 1)` subsampling factors.
+    #[must_use]
+    pub fn exported_bayer_pattern(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+    ) -> Option<BayerPattern> {
+        match self.color_mode {
+            ColorMode::Bayer(pattern) if factors.0 % 2 == 1 && factors.1 % 2 == 1 => {
+                Some(pattern.at(window.x, window.y))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns an iterator over the canvas window image scanlines.
     ///
     /// The iteration starts from the window origin and goes in the positive Y direction.
-    /// Each window scanline is represented as a pixel span (`&[Pixel]` slice).
+    /// Each window scanline is represented as a pixel span (`&[P]` slice).
     ///
     /// # Errors
     ///
     /// Returns `None` is the window rectangle origin or dimensions
     /// are out of the canvas bounds.
     #[must_use]
-    pub fn window_spans(&self, window: Window) -> Option<WindowSpans<'_>> {
+    pub fn window_spans(&self, window: Window) -> Option<WindowSpans<'_, P>> {
         if !window.is_inside(self.width, self.height) {
             return None;
         }
@@ -268,17 +625,69 @@ impl Canvas {
     ///
     /// Returns [`EncoderError::NotImplemented`] if the requested image format
     /// is not yet supported.
-    #[cfg(not(feature = "png"))]
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
     pub fn export_image(&self, format: ImageFormat) -> Result<Vec<u8>, EncoderError> {
+        let mut buf = Vec::with_capacity(
+            format.buffer_capacity(self.row_len(self.color_mode) * self.height as usize),
+        );
+
+        self.export_image_to(format, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Exports the canvas contents in the requested image format
+    /// into a caller-provided writer.
+    ///
+    /// The encoded image data is written directly to `writer`
+    /// without allocating the output buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn export_image_to<W: Write>(
+        &self,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
         // Export the entire canvas.
         let window = Window::new(self.width, self.height);
 
-        match format {
-            ImageFormat::RawGamma8Bpp => self.export_raw8bpp(window),
-            ImageFormat::RawLinear10BppLE => self.export_raw1xbpp::<10>(window),
-            ImageFormat::RawLinear12BppLE => self.export_raw1xbpp::<12>(window),
-            _ => Err(EncoderError::NotImplemented),
-        }
+        self.export_window_image_to(window, format, writer)
+    }
+
+    /// Exports the canvas contents in the requested RAW image format
+    /// into a caller-provided byte buffer.
+    ///
+    /// Returns the number of bytes written to the beginning of `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::UnsupportedFormat`] if the requested image format
+    /// is not a RAW image format.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::BufferTooSmall`] if the encoded image does not fit
+    /// into `buf`.
+    pub fn export_image_into(
+        &self,
+        format: ImageFormat,
+        buf: &mut [u8],
+    ) -> Result<usize, EncoderError> {
+        // Export the entire canvas.
+        let window = Window::new(self.width, self.height);
+
+        self.export_window_image_into(window, format, buf)
     }
 
     /// Exports the canvas window image in the requested image format.
@@ -290,95 +699,160 @@ impl Canvas {
     ///
     /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
     /// or dimensions are out of the canvas bounds.
-    #[cfg(not(feature = "png"))]
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
     pub fn export_window_image(
         &self,
         window: Window,
         format: ImageFormat,
     ) -> Result<Vec<u8>, EncoderError> {
+        let mut buf = Vec::with_capacity(format.buffer_capacity(window.len()));
+
+        self.export_window_image_to(window, format, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Exports the canvas window image in the requested image format
+    /// into a caller-provided writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn export_window_image_to<W: Write>(
+        &self,
+        window: Window,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
         if !window.is_inside(self.width, self.height) {
             return Err(EncoderError::BrokenWindow);
         }
 
-        match format {
-            ImageFormat::RawGamma8Bpp => self.export_raw8bpp(window),
-            ImageFormat::RawLinear10BppLE => self.export_raw1xbpp::<10>(window),
-            ImageFormat::RawLinear12BppLE => self.export_raw1xbpp::<12>(window),
-            _ => Err(EncoderError::NotImplemented),
-        }
+        self.validate_color_mode(format)?;
+
+        self.encode_window_image(window, format, writer)
     }
 
-    /// Exports the subsampled canvas image in the requested image format.
+    /// Exports the canvas window image in the requested RAW image format
+    /// into a caller-provided byte buffer.
     ///
-    /// The integer subsampling factors in X and Y directions
-    /// are passed in `factors`.
+    /// Returns the number of bytes written to the beginning of `buf`.
     ///
     /// # Errors
     ///
-    /// Returns [`EncoderError::NotImplemented`] if the requested image format
-    /// is not yet supported.
-    #[cfg(not(feature = "png"))]
-    pub fn export_subsampled_image(
+    /// Returns [`EncoderError::UnsupportedFormat`] if the requested image format
+    /// is not a RAW image format.
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::BufferTooSmall`] if the encoded image does not fit
+    /// into `buf`.
+    pub fn export_window_image_into(
         &self,
-        factors: (u32, u32),
+        window: Window,
         format: ImageFormat,
-    ) -> Result<Vec<u8>, EncoderError> {
-        validate_subsampling_rate(factors)?;
-
-        match format {
-            ImageFormat::RawGamma8Bpp => self.export_sub_raw8bpp(factors),
-            ImageFormat::RawLinear10BppLE => self.export_sub_raw1xbpp::<10>(factors),
-            ImageFormat::RawLinear12BppLE => self.export_sub_raw1xbpp::<12>(factors),
-            _ => Err(EncoderError::NotImplemented),
+        buf: &mut [u8],
+    ) -> Result<usize, EncoderError> {
+        if !window.is_inside(self.width, self.height) {
+            return Err(EncoderError::BrokenWindow);
         }
+
+        let size = format
+            .raw_image_size(window.len())
+            .ok_or(EncoderError::UnsupportedFormat)?;
+
+        // The buffer size depends on the color mode.
+        self.validate_color_mode(format)?;
+
+        let rawbuf = buf.get_mut(..size).ok_or(EncoderError::BufferTooSmall)?;
+
+        self.export_window_image_to(window, format, rawbuf)?;
+
+        Ok(size)
     }
 
-    /// Exports the canvas contents in the requested image format.
+    /// Exports multiple canvas window images in the requested image format.
+    ///
+    /// Returns a separately encoded image for each window in `windows`.
     ///
     /// # Errors
     ///
     /// Returns [`EncoderError::NotImplemented`] if the requested image format
     /// is not yet supported.
-    #[cfg(feature = "png")]
-    pub fn export_image(&self, format: ImageFormat) -> Result<Vec<u8>, EncoderError> {
-        // Export the entire canvas.
-        let window = Window::new(self.width, self.height);
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if any window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::OverlappingWindows`] if any two window
+    /// rectangles overlap.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    pub fn export_window_images(
+        &self,
+        windows: &[Window],
+        format: ImageFormat,
+    ) -> Result<Vec<Vec<u8>>, EncoderError> {
+        self.validate_windows(windows)?;
 
-        match format {
-            ImageFormat::RawGamma8Bpp => self.export_raw8bpp(window),
-            ImageFormat::RawLinear10BppLE => self.export_raw1xbpp::<10>(window),
-            ImageFormat::RawLinear12BppLE => self.export_raw1xbpp::<12>(window),
-            ImageFormat::PngGamma8Bpp => self.export_png8bpp(window),
-            ImageFormat::PngLinear16Bpp => self.export_png16bpp(window),
-        }
+        windows
+            .iter()
+            .map(|&window| self.export_window_image(window, format))
+            .collect()
     }
 
-    /// Exports the canvas window image in the requested image format.
+    /// Exports multiple canvas window images in the requested image format
+    /// concatenated into a single byte buffer.
+    ///
+    /// Returns the concatenated image data and the offset table
+    /// containing the byte offset of each window image in the buffer.
+    /// The window images are stored in the `windows` order.
     ///
     /// # Errors
     ///
     /// Returns [`EncoderError::NotImplemented`] if the requested image format
     /// is not yet supported.
     ///
-    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// Returns [`EncoderError::BrokenWindow`] if any window rectangle origin
     /// or dimensions are out of the canvas bounds.
-    #[cfg(feature = "png")]
-    pub fn export_window_image(
+    ///
+    /// Returns [`EncoderError::OverlappingWindows`] if any two window
+    /// rectangles overlap.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    pub fn export_concatenated_window_images(
         &self,
-        window: Window,
+        windows: &[Window],
         format: ImageFormat,
-    ) -> Result<Vec<u8>, EncoderError> {
-        if !window.is_inside(self.width, self.height) {
-            return Err(EncoderError::BrokenWindow);
-        }
+    ) -> Result<(Vec<u8>, Vec<usize>), EncoderError> {
+        self.validate_windows(windows)?;
 
-        match format {
-            ImageFormat::RawGamma8Bpp => self.export_raw8bpp(window),
-            ImageFormat::RawLinear10BppLE => self.export_raw1xbpp::<10>(window),
-            ImageFormat::RawLinear12BppLE => self.export_raw1xbpp::<12>(window),
-            ImageFormat::PngGamma8Bpp => self.export_png8bpp(window),
-            ImageFormat::PngLinear16Bpp => self.export_png16bpp(window),
+        let pixlen = windows.iter().map(Window::len).sum();
+        let mut buf = Vec::with_capacity(format.buffer_capacity(pixlen));
+        let mut offsets = Vec::with_capacity(windows.len());
+
+        for &window in windows {
+            offsets.push(buf.len());
+            self.export_window_image_to(window, format, &mut buf)?;
         }
+
+        Ok((buf, offsets))
     }
 
     /// Exports the subsampled canvas image in the requested image format.
@@ -390,7 +864,12 @@ impl Canvas {
     ///
     /// Returns [`EncoderError::NotImplemented`] if the requested image format
     /// is not yet supported.
-    #[cfg(feature = "png")]
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
     pub fn export_subsampled_image(
         &self,
         factors: (u32, u32),
@@ -398,22 +877,528 @@ impl Canvas {
     ) -> Result<Vec<u8>, EncoderError> {
         validate_subsampling_rate(factors)?;
 
-        match format {
-            ImageFormat::RawGamma8Bpp => self.export_sub_raw8bpp(factors),
-            ImageFormat::RawLinear10BppLE => self.export_sub_raw1xbpp::<10>(factors),
-            ImageFormat::RawLinear12BppLE => self.export_sub_raw1xbpp::<12>(factors),
-            ImageFormat::PngGamma8Bpp => self.export_sub_png8bpp(factors),
-            ImageFormat::PngLinear16Bpp => self.export_sub_png16bpp(factors),
-        }
-    }
-}
+        let pixlen = Self::subsampled_len(Window::new(self.width, self.height), factors);
+        let mut buf = Vec::with_capacity(format.buffer_capacity(pixlen));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::SpotShape;
+        self.export_subsampled_image_to(factors, format, &mut buf)?;
 
-    #[cfg(not(feature = "png"))]
+        Ok(buf)
+    }
+
+    /// Exports the subsampled canvas image in the requested image format
+    /// into a caller-provided writer.
+    ///
+    /// The integer subsampling factors in X and Y directions
+    /// are passed in `factors`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn export_subsampled_image_to<W: Write>(
+        &self,
+        factors: (u32, u32),
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        validate_subsampling_rate(factors)?;
+        self.validate_color_mode(format)?;
+
+        let window = Window::new(self.width, self.height);
+
+        self.encode_subsampled_image(window, factors, None, format, writer)
+    }
+
+    /// Exports the subsampled canvas image in the requested RAW image format
+    /// into a caller-provided byte buffer.
+    ///
+    /// The integer subsampling factors in X and Y directions
+    /// are passed in `factors`.
+    ///
+    /// Returns the number of bytes written to the beginning of `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::UnsupportedFormat`] if the requested image format
+    /// is not a RAW image format.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::BufferTooSmall`] if the encoded image does not fit
+    /// into `buf`.
+    pub fn export_subsampled_image_into(
+        &self,
+        factors: (u32, u32),
+        format: ImageFormat,
+        buf: &mut [u8],
+    ) -> Result<usize, EncoderError> {
+        validate_subsampling_rate(factors)?;
+
+        let size = format
+            .raw_image_size(Self::subsampled_len(
+                Window::new(self.width, self.height),
+                factors,
+            ))
+            .ok_or(EncoderError::UnsupportedFormat)?;
+
+        // The buffer size depends on the color mode.
+        self.validate_color_mode(format)?;
+
+        let rawbuf = buf.get_mut(..size).ok_or(EncoderError::BufferTooSmall)?;
+
+        self.export_subsampled_image_to(factors, format, rawbuf)?;
+
+        Ok(size)
+    }
+
+    /// Exports the subsampled canvas window image in the requested image format.
+    ///
+    /// The integer subsampling factors in X and Y directions
+    /// are passed in `factors`. The window dimensions must be
+    /// divisible by the respective subsampling factors.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero, or do not divide the window dimensions.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    pub fn export_subsampled_window_image(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        format: ImageFormat,
+    ) -> Result<Vec<u8>, EncoderError> {
+        self.validate_subsampled_window(window, factors)?;
+
+        let pixlen = Self::subsampled_len(window, factors);
+        let mut buf = Vec::with_capacity(format.buffer_capacity(pixlen));
+
+        self.export_subsampled_window_image_to(window, factors, format, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Exports the subsampled canvas window image in the requested image format
+    /// into a caller-provided writer.
+    ///
+    /// The integer subsampling factors in X and Y directions
+    /// are passed in `factors`. The window dimensions must be
+    /// divisible by the respective subsampling factors.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero, or do not divide the window dimensions.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn export_subsampled_window_image_to<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        self.validate_subsampled_window(window, factors)?;
+        self.validate_color_mode(format)?;
+
+        self.encode_subsampled_image(window, factors, None, format, writer)
+    }
+
+    /// Exports the subsampled canvas window image in the requested RAW image format
+    /// into a caller-provided byte buffer.
+    ///
+    /// The integer subsampling factors in X and Y directions
+    /// are passed in `factors`. The window dimensions must be
+    /// divisible by the respective subsampling factors.
+    ///
+    /// Returns the number of bytes written to the beginning of `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::UnsupportedFormat`] if the requested image format
+    /// is not a RAW image format.
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the window rectangle origin
+    /// or dimensions are out of the canvas bounds.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the subsampling
+    /// factors are too large or zero, or do not divide the window dimensions.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::BufferTooSmall`] if the encoded image does not fit
+    /// into `buf`.
+    pub fn export_subsampled_window_image_into(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        format: ImageFormat,
+        buf: &mut [u8],
+    ) -> Result<usize, EncoderError> {
+        self.validate_subsampled_window(window, factors)?;
+
+        let size = format
+            .raw_image_size(Self::subsampled_len(window, factors))
+            .ok_or(EncoderError::UnsupportedFormat)?;
+
+        // The buffer size depends on the color mode.
+        self.validate_color_mode(format)?;
+
+        let rawbuf = buf.get_mut(..size).ok_or(EncoderError::BufferTooSmall)?;
+
+        self.export_subsampled_window_image_to(window, factors, format, rawbuf)?;
+
+        Ok(size)
+    }
+
+    /// Exports the binned canvas image in the requested image format.
+    ///
+    /// The integer binning factors in X and Y directions
+    /// are passed in `factors`. The pixel values in each binning block
+    /// are combined according to the `binning` mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the binning
+    /// factors are too large or zero.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    pub fn export_binned_image(
+        &self,
+        factors: (u32, u32),
+        binning: Binning,
+        format: ImageFormat,
+    ) -> Result<Vec<u8>, EncoderError> {
+        validate_subsampling_rate(factors)?;
+
+        let pixlen = Self::subsampled_len(Window::new(self.width, self.height), factors);
+        let mut buf = Vec::with_capacity(format.buffer_capacity(pixlen));
+
+        self.export_binned_image_to(factors, binning, format, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Exports the binned canvas image in the requested image format
+    /// into a caller-provided writer.
+    ///
+    /// The integer binning factors in X and Y directions
+    /// are passed in `factors`. The pixel values in each binning block
+    /// are combined according to the `binning` mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not yet supported.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the binning
+    /// factors are too large or zero.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn export_binned_image_to<W: Write>(
+        &self,
+        factors: (u32, u32),
+        binning: Binning,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        validate_subsampling_rate(factors)?;
+        self.validate_color_mode(format)?;
+
+        let window = Window::new(self.width, self.height);
+
+        self.encode_subsampled_image(window, factors, Some(binning), format, writer)
+    }
+
+    /// Exports the binned canvas image in the requested RAW image format
+    /// into a caller-provided byte buffer.
+    ///
+    /// The integer binning factors in X and Y directions
+    /// are passed in `factors`. The pixel values in each binning block
+    /// are combined according to the `binning` mode.
+    ///
+    /// Returns the number of bytes written to the beginning of `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::UnsupportedFormat`] if the requested image format
+    /// is not a RAW image format.
+    ///
+    /// Returns [`EncoderError::InvalidSubsamplingRate`] if the binning
+    /// factors are too large or zero.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::BufferTooSmall`] if the encoded image does not fit
+    /// into `buf`.
+    pub fn export_binned_image_into(
+        &self,
+        factors: (u32, u32),
+        binning: Binning,
+        format: ImageFormat,
+        buf: &mut [u8],
+    ) -> Result<usize, EncoderError> {
+        validate_subsampling_rate(factors)?;
+
+        let size = format
+            .raw_image_size(Self::subsampled_len(
+                Window::new(self.width, self.height),
+                factors,
+            ))
+            .ok_or(EncoderError::UnsupportedFormat)?;
+
+        // The buffer size depends on the color mode.
+        self.validate_color_mode(format)?;
+
+        let rawbuf = buf.get_mut(..size).ok_or(EncoderError::BufferTooSmall)?;
+
+        self.export_binned_image_to(factors, binning, format, rawbuf)?;
+
+        Ok(size)
+    }
+
+    /// Calculates the subsampled canvas window image size in pixels.
+    #[must_use]
+    fn subsampled_len(window: Window, factors: (u32, u32)) -> usize {
+        ((window.w / factors.0) * (window.h / factors.1)) as usize
+    }
+
+    /// Evaluates the subsampled canvas window image sample value.
+    ///
+    /// `pos` are the subsampled image pixel coordinates relative
+    /// to the window origin, `channel` is the interleaved color
+    /// channel sample index.
+    ///
+    /// Picks the top left pixel of the subsampling block
+    /// if no `binning` mode is specified.
+    #[must_use]
+    fn subsampled_sample(
+        &self,
+        window: Window,
+        pos: (u32, u32),
+        channel: usize,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+    ) -> Pixel {
+        let channels = self.color_mode.channels();
+        let sample = |x: u32, y: u32| {
+            let offset = (window.y + y) as usize * self.stride + (window.x + x) as usize * channels;
+            self.pixbuf[offset + channel].to_u16()
+        };
+
+        let (x, y) = pos;
+
+        let Some(binning) = binning else {
+            return sample(x * factors.0, y * factors.1);
+        };
+
+        // Binning block origin and pixel pitch in window coordinates
+        let (x0, y0, step) = if let ColorMode::Bayer(_) = self.color_mode {
+            // Bin the same color pixels within the 2x2 mosaic cell superblocks.
+            let bin_origin = |v: u32, k: u32| 2 * k * (v / 2) + v % 2;
+            (bin_origin(x, factors.0), bin_origin(y, factors.1), 2)
+        } else {
+            (x * factors.0, y * factors.1, 1)
+        };
+
+        let mut sum = 0u32;
+        let mut count = 0u32;
+
+        // Partial superblocks are clipped at the window edges.
+        for i in (0..factors.1)
+            .map(|i| y0 + i * step)
+            .take_while(|&i| i < window.h)
+        {
+            for j in (0..factors.0)
+                .map(|j| x0 + j * step)
+                .take_while(|&j| j < window.w)
+            {
+                sum += u32::from(sample(j, i));
+                count += 1;
+            }
+        }
+
+        let value = match binning {
+            Binning::Sum => sum,
+            Binning::Average => (sum + count / 2) / count,
+        };
+
+        // Saturate to the maximum pixel value.
+        Pixel::try_from(value).unwrap_or(Pixel::MAX)
+    }
+
+    /// Encodes the canvas window image in the requested image format.
+    ///
+    /// The window and the image format are validated by the caller.
+    #[cfg(not(feature = "png"))]
+    fn encode_window_image<W: Write>(
+        &self,
+        window: Window,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        match format {
+            ImageFormat::RawGamma8Bpp => self.export_raw8bpp(window, writer),
+            ImageFormat::RawLinear10BppLE => self.export_raw1xbpp::<10, W>(window, writer),
+            ImageFormat::RawLinear12BppLE => self.export_raw1xbpp::<12, W>(window, writer),
+            ImageFormat::RawLinear16BppLE => self.export_raw1xbpp::<16, W>(window, writer),
+            ImageFormat::NpyLinear16Bpp => self.export_npy(window, NpyDtype::U16, writer),
+            ImageFormat::NpyLinearFloat => self.export_npy(window, NpyDtype::F32, writer),
+            ImageFormat::TiffLinearFloat => self.export_tiff_float(window, writer),
+            _ => Err(EncoderError::NotImplemented),
+        }
+    }
+
+    /// Encodes the subsampled canvas window image in the requested image format.
+    ///
+    /// Uses pixel binning instead of decimation if the `binning` mode is specified.
+    ///
+    /// The window, the subsampling factors and the image format
+    /// are validated by the caller.
+    #[cfg(not(feature = "png"))]
+    fn encode_subsampled_image<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        match format {
+            ImageFormat::RawGamma8Bpp => self.export_sub_raw8bpp(window, factors, binning, writer),
+            ImageFormat::RawLinear10BppLE => {
+                self.export_sub_raw1xbpp::<10, W>(window, factors, binning, writer)
+            }
+            ImageFormat::RawLinear12BppLE => {
+                self.export_sub_raw1xbpp::<12, W>(window, factors, binning, writer)
+            }
+            ImageFormat::RawLinear16BppLE => {
+                self.export_sub_raw1xbpp::<16, W>(window, factors, binning, writer)
+            }
+            ImageFormat::NpyLinear16Bpp => {
+                self.export_sub_npy(window, factors, binning, NpyDtype::U16, writer)
+            }
+            ImageFormat::NpyLinearFloat => {
+                self.export_sub_npy(window, factors, binning, NpyDtype::F32, writer)
+            }
+            _ => Err(EncoderError::NotImplemented),
+        }
+    }
+
+    /// Encodes the canvas window image in the requested image format.
+    ///
+    /// The window and the image format are validated by the caller.
+    #[cfg(feature = "png")]
+    fn encode_window_image<W: Write>(
+        &self,
+        window: Window,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        match format {
+            ImageFormat::RawGamma8Bpp => self.export_raw8bpp(window, writer),
+            ImageFormat::RawLinear10BppLE => self.export_raw1xbpp::<10, W>(window, writer),
+            ImageFormat::RawLinear12BppLE => self.export_raw1xbpp::<12, W>(window, writer),
+            ImageFormat::RawLinear16BppLE => self.export_raw1xbpp::<16, W>(window, writer),
+            ImageFormat::NpyLinear16Bpp => self.export_npy(window, NpyDtype::U16, writer),
+            ImageFormat::NpyLinearFloat => self.export_npy(window, NpyDtype::F32, writer),
+            ImageFormat::TiffLinearFloat => self.export_tiff_float(window, writer),
+            ImageFormat::PngGamma8Bpp | ImageFormat::PngRgbGamma8Bpp => {
+                self.export_png8bpp(window, writer)
+            }
+            ImageFormat::PngLinear16Bpp | ImageFormat::PngRgbLinear16Bpp => {
+                self.export_png16bpp(window, writer)
+            }
+        }
+    }
+
+    /// Encodes the subsampled canvas window image in the requested image format.
+    ///
+    /// Uses pixel binning instead of decimation if the `binning` mode is specified.
+    ///
+    /// The window, the subsampling factors and the image format
+    /// are validated by the caller.
+    #[cfg(feature = "png")]
+    fn encode_subsampled_image<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+        format: ImageFormat,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        match format {
+            ImageFormat::RawGamma8Bpp => self.export_sub_raw8bpp(window, factors, binning, writer),
+            ImageFormat::RawLinear10BppLE => {
+                self.export_sub_raw1xbpp::<10, W>(window, factors, binning, writer)
+            }
+            ImageFormat::RawLinear12BppLE => {
+                self.export_sub_raw1xbpp::<12, W>(window, factors, binning, writer)
+            }
+            ImageFormat::RawLinear16BppLE => {
+                self.export_sub_raw1xbpp::<16, W>(window, factors, binning, writer)
+            }
+            ImageFormat::NpyLinear16Bpp => {
+                self.export_sub_npy(window, factors, binning, NpyDtype::U16, writer)
+            }
+            ImageFormat::NpyLinearFloat => {
+                self.export_sub_npy(window, factors, binning, NpyDtype::F32, writer)
+            }
+            ImageFormat::TiffLinearFloat => Err(EncoderError::NotImplemented),
+            ImageFormat::PngGamma8Bpp | ImageFormat::PngRgbGamma8Bpp => {
+                self.export_sub_png8bpp(window, factors, binning, writer)
+            }
+            ImageFormat::PngLinear16Bpp | ImageFormat::PngRgbLinear16Bpp => {
+                self.export_sub_png16bpp(window, factors, binning, writer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpotShape;
+
+    #[cfg(not(feature = "png"))]
     #[test]
     fn image_format_error() {
         let c = Canvas::new(0, 0);
@@ -453,6 +1438,285 @@ mod tests {
         );
     }
 
+    #[test]
+    fn color_mode_error() {
+        let mut c = Canvas::new(10, 10);
+
+        assert_eq!(
+            c.export_image(ImageFormat::PngRgbGamma8Bpp),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+
+        c.set_color_mode(ColorMode::Rgb);
+
+        assert_eq!(
+            c.export_window_image(Window::new(4, 4), ImageFormat::RawGamma8Bpp),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+        assert_eq!(
+            c.export_subsampled_image((2, 2), ImageFormat::RawLinear10BppLE),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+    }
+
+    #[test]
+    fn get_rgb_window_spans() {
+        let mut c = Canvas::new(100, 100);
+
+        c.set_color_mode(ColorMode::Rgb);
+        let spot = c.add_spot((50.75, 50.5), SpotShape::default(), 1.0);
+        c.set_spot_color(spot, crate::SpotColor::new(1.0, 0.0, 0.5));
+        c.draw();
+
+        let wnd = Window::new(4, 3).at(50, 50);
+
+        let mut spans = c.window_spans(wnd).unwrap();
+        assert_eq!(spans.len(), 3);
+
+        let span = spans.next().unwrap();
+        assert_eq!(span.len(), 3 * 4);
+        assert_eq!(span[..6], [542, 0, 271, 18087, 0, 9043]);
+    }
+
+    #[test]
+    fn bayer_pattern_ids() {
+        let mut c = Canvas::new(16, 16);
+        let wnd = Window::new(8, 8).at(3, 2);
+
+        assert_eq!(c.exported_bayer_pattern(wnd, (1, 1)), None);
+
+        c.set_color_mode(ColorMode::Bayer(BayerPattern::Rggb));
+
+        assert_eq!(
+            c.exported_bayer_pattern(wnd, (1, 1)),
+            Some(BayerPattern::Grbg)
+        );
+        assert_eq!(
+            c.exported_bayer_pattern(wnd.at(0, 0), (3, 5)),
+            Some(BayerPattern::Rggb)
+        );
+        assert_eq!(c.exported_bayer_pattern(wnd, (2, 1)), None);
+    }
+
+    #[test]
+    fn binned_samples() {
+        let mut c = Canvas::new(4, 4);
+        c.pixbuf = (0..16).map(|v| v * 1000).collect();
+
+        let full = Window::new(4, 4);
+
+        // Decimation picks the top left block pixel.
+        assert_eq!(c.subsampled_sample(full, (1, 1), 0, (2, 2), None), 10000);
+
+        // Block (1, 1) = [10, 11, 14, 15] * 1000
+        let sum = Some(Binning::Sum);
+        let avg = Some(Binning::Average);
+        assert_eq!(c.subsampled_sample(full, (1, 1), 0, (2, 2), sum), 50000);
+        assert_eq!(c.subsampled_sample(full, (1, 1), 0, (2, 2), avg), 12500);
+
+        // The sums are saturated.
+        assert_eq!(
+            c.subsampled_sample(full, (0, 0), 0, (4, 4), sum),
+            Pixel::MAX
+        );
+        assert_eq!(c.subsampled_sample(full, (0, 0), 0, (4, 4), avg), 7500);
+
+        // Only the same color pixels are binned in the Bayer color mode:
+        // pixel (1, 0) of the binned image = [1, 3, 9, 11] * 1000
+        c.color_mode = ColorMode::Bayer(BayerPattern::Rggb);
+        assert_eq!(c.subsampled_sample(full, (1, 0), 0, (2, 2), avg), 6000);
+        assert_eq!(c.subsampled_sample(full, (1, 1), 0, (2, 2), avg), 10000);
+
+        // The partial binning blocks are clipped at the canvas edges.
+        assert_eq!(c.subsampled_sample(full, (1, 0), 0, (3, 3), avg), 6000);
+    }
+
+    #[test]
+    fn export_binned_images() {
+        let mut c = Canvas::new(32, 32);
+        c.set_background(1000);
+        c.draw();
+
+        let img = c
+            .export_binned_image((4, 2), Binning::Sum, ImageFormat::RawLinear12BppLE)
+            .unwrap();
+        assert_eq!(img.len(), 2 * 8 * 16);
+        assert_eq!(img[..2], (8000u16 >> 4).to_le_bytes());
+
+        let mut buf = [0u8; 64];
+        assert_eq!(
+            c.export_binned_image_into(
+                (4, 4),
+                Binning::Average,
+                ImageFormat::RawGamma8Bpp,
+                &mut buf
+            ),
+            Ok(64)
+        );
+        assert_eq!(
+            buf[..],
+            c.export_subsampled_image((4, 4), ImageFormat::RawGamma8Bpp)
+                .unwrap()
+        );
+
+        assert_eq!(
+            c.export_binned_image((0, 2), Binning::Sum, ImageFormat::RawGamma8Bpp),
+            Err(EncoderError::InvalidSubsamplingRate)
+        );
+    }
+
+    #[test]
+    fn subsampled_window_errors() {
+        let c = Canvas::new(64, 32);
+        let fmt = ImageFormat::RawGamma8Bpp;
+
+        let wnd = Window::new(16, 8).at(50, 10);
+        assert_eq!(
+            c.export_subsampled_window_image(wnd, (2, 2), fmt),
+            Err(EncoderError::BrokenWindow)
+        );
+
+        let wnd = Window::new(16, 8).at(40, 10);
+        assert_eq!(
+            c.export_subsampled_window_image(wnd, (3, 2), fmt),
+            Err(EncoderError::InvalidSubsamplingRate)
+        );
+        assert_eq!(
+            c.export_subsampled_window_image(wnd, (2, 0), fmt),
+            Err(EncoderError::InvalidSubsamplingRate)
+        );
+        assert_eq!(
+            c.export_subsampled_window_image(wnd, (4, 8), fmt)
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn export_subsampled_windows() {
+        let mut c = Canvas::new(32, 32);
+        c.pixbuf = (0..32 * 32).map(|v| v * 64).collect();
+
+        let fmt = ImageFormat::RawLinear12BppLE;
+        let wnd = Window::new(8, 4).at(5, 3);
+
+        let mut buf = [0u8; 16];
+        assert_eq!(
+            c.export_subsampled_window_image_into(wnd, (2, 2), fmt, &mut buf),
+            Ok(16)
+        );
+
+        // 12-bit canvas pixel sample value at (x, y)
+        let sample = |x: u16, y: u16| (((y * 32 + x) * 64) >> 4).to_le_bytes();
+
+        // The window image samples are picked from the window origin.
+        assert_eq!(buf[..2], sample(5, 3));
+        assert_eq!(buf[2..4], sample(7, 3));
+        assert_eq!(buf[8..10], sample(5, 5));
+
+        // The full canvas window gives the whole subsampled image.
+        let full = Window::new(32, 32);
+        assert_eq!(
+            c.export_subsampled_window_image(full, (4, 2), fmt),
+            c.export_subsampled_image((4, 2), fmt)
+        );
+    }
+
+    #[test]
+    fn multi_window_errors() {
+        let c = Canvas::new(64, 32);
+        let fmt = ImageFormat::RawGamma8Bpp;
+
+        let wnd1 = Window::new(16, 8).at(10, 10);
+        let wnd2 = Window::new(16, 8).at(26, 17);
+        let wnd3 = Window::new(16, 8).at(50, 20);
+
+        assert_eq!(
+            c.export_window_images(&[wnd1, wnd3], fmt),
+            Err(EncoderError::BrokenWindow)
+        );
+        assert_eq!(
+            c.export_concatenated_window_images(&[wnd1, wnd2, wnd1.at(20, 5)], fmt),
+            Err(EncoderError::OverlappingWindows)
+        );
+
+        // Touching windows do not overlap.
+        assert!(c
+            .export_window_images(&[wnd1, wnd2, wnd1.at(0, 18)], fmt)
+            .is_ok());
+    }
+
+    #[test]
+    fn export_multiple_windows() {
+        let mut c = Canvas::new(32, 32);
+        c.pixbuf = (0..32 * 32).map(|v| v * 64).collect();
+
+        let fmt = ImageFormat::RawLinear10BppLE;
+        let windows = [Window::new(4, 2).at(20, 3), Window::new(3, 3).at(1, 1)];
+
+        let images = c.export_window_images(&windows, fmt).unwrap();
+        let (buf, offsets) = c.export_concatenated_window_images(&windows, fmt).unwrap();
+
+        assert_eq!(offsets, [0, 16]);
+        assert_eq!(buf.len(), 16 + 18);
+        assert_eq!(buf[..16], images[0]);
+        assert_eq!(buf[16..], images[1]);
+        assert_eq!(images[1], c.export_window_image(windows[1], fmt).unwrap());
+    }
+
+    #[test]
+    fn buffer_size_error() {
+        let c = Canvas::new(10, 10);
+        let mut buf = [0u8; 150];
+
+        assert_eq!(
+            c.export_image_into(ImageFormat::RawLinear10BppLE, &mut buf),
+            Err(EncoderError::BufferTooSmall)
+        );
+        assert_eq!(
+            c.export_image_into(ImageFormat::RawGamma8Bpp, &mut buf),
+            Ok(100)
+        );
+        assert_eq!(
+            c.export_window_image_into(Window::new(10, 8), ImageFormat::RawLinear12BppLE, &mut buf),
+            Err(EncoderError::BufferTooSmall)
+        );
+        assert_eq!(
+            c.export_subsampled_image_into((2, 1), ImageFormat::RawLinear12BppLE, &mut buf),
+            Ok(100)
+        );
+        assert_eq!(
+            c.export_image_into(ImageFormat::PngGamma8Bpp, &mut buf),
+            Err(EncoderError::UnsupportedFormat)
+        );
+        assert_eq!(
+            c.export_window_image_into(Window::new(4, 4), ImageFormat::NpyLinear16Bpp, &mut buf),
+            Err(EncoderError::UnsupportedFormat)
+        );
+        assert_eq!(
+            c.export_subsampled_image_into((2, 2), ImageFormat::TiffLinearFloat, &mut buf),
+            Err(EncoderError::UnsupportedFormat)
+        );
+
+        // The color mode is checked before the buffer size.
+        let mut c = Canvas::new(10, 10);
+        c.set_color_mode(ColorMode::Rgb);
+        assert_eq!(
+            c.export_image_into(ImageFormat::RawLinear16BppLE, &mut buf),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+        assert_eq!(
+            c.export_binned_image_into(
+                (2, 2),
+                Binning::Sum,
+                ImageFormat::RawGamma8Bpp,
+                &mut buf[..8]
+            ),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+    }
+
     #[test]
     fn window_ops() {
         let wnd = Window::new(128, 64).at(200, 100);
@@ -492,6 +1756,88 @@ mod tests {
         assert_eq!(vec, [0, 0, 542, 18087, 0, 0, 193, 731, 0, 0, 0, 0]);
     }
 
+    #[test]
+    fn export_strided_images() {
+        let mkimage = |stride| {
+            let mut c = Canvas::new(50, 40);
+            c.set_background(1000);
+            c.set_hdr_rendering(true);
+            c.set_row_stride(stride).unwrap();
+            c.add_spot((20.6, 15.2), SpotShape::default().scale(3.5), 1.5);
+
+            c.draw();
+            c
+        };
+
+        let packed = mkimage(50);
+        let strided = mkimage(64);
+
+        let wnd = Window::new(17, 9).at(11, 8);
+        assert!(packed
+            .window_spans(wnd)
+            .unwrap()
+            .eq(strided.window_spans(wnd).unwrap()));
+
+        for i in 0..40 {
+            let row = &strided.pixels()[i * 64..(i + 1) * 64];
+            assert_eq!(row[..50], packed.pixels()[i * 50..(i + 1) * 50]);
+            assert!(row[50..].iter().all(|&p| p == 1000));
+        }
+
+        for fmt in [
+            ImageFormat::RawGamma8Bpp,
+            ImageFormat::RawLinear12BppLE,
+            ImageFormat::NpyLinearFloat,
+            ImageFormat::TiffLinearFloat,
+        ] {
+            assert_eq!(strided.export_image(fmt), packed.export_image(fmt));
+            assert_eq!(
+                strided.export_window_image(wnd, fmt),
+                packed.export_window_image(wnd, fmt)
+            );
+        }
+
+        let fmt = ImageFormat::RawLinear16BppLE;
+        assert_eq!(
+            strided.export_subsampled_image((3, 2), fmt),
+            packed.export_subsampled_image((3, 2), fmt)
+        );
+        assert_eq!(
+            strided.export_binned_image((2, 2), Binning::Average, fmt),
+            packed.export_binned_image((2, 2), Binning::Average, fmt)
+        );
+    }
+
+    #[test]
+    fn export_strided_rgb_images() {
+        let mkimage = |stride| {
+            let mut c = Canvas::new(20, 10);
+            c.set_color_mode(ColorMode::Rgb);
+            c.set_row_stride(stride).unwrap();
+
+            let spot = c.add_spot((8.3, 4.6), SpotShape::default().scale(2.0), 0.8);
+            c.set_spot_color(spot, crate::SpotColor::new(1.0, 0.2, 0.5));
+
+            c.draw();
+            c
+        };
+
+        let packed = mkimage(3 * 20);
+        let strided = mkimage(3 * 24);
+
+        let wnd = Window::new(12, 6).at(2, 1);
+        assert!(packed
+            .window_spans(wnd)
+            .unwrap()
+            .eq(strided.window_spans(wnd).unwrap()));
+
+        #[cfg(feature = "png")]
+        assert_eq!(
+            strided.export_subsampled_image((2, 2), ImageFormat::PngRgbLinear16Bpp),
+            packed.export_subsampled_image((2, 2), ImageFormat::PngRgbLinear16Bpp)
+        );
+    }
+
     #[test]
     fn broken_windows() {
         let c = Canvas::new(100, 100);
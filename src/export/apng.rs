@@ -0,0 +1,269 @@
+//! Planetarium
+//! ===========
+//!
+//! Animated PNG image export routines
+//! ----------------------------------
+//!
+//! This module is gated by the "png" feature.
+//!
+//! Defines the public animated PNG image sequence writer `ApngWriter`.
+
+use std::io::Write;
+
+use png::{BitDepth, Encoder, ScaledFloat, Writer};
+
+use super::png::add_cfa_pattern;
+use crate::{Canvas, CanvasPixel, EncoderError, ImageFormat, Window};
+
+/// Animated PNG (APNG) image sequence writer
+///
+/// Encodes the successive canvas images as animated PNG frames.
+/// The animation header settings are taken from the canvas passed
+/// to `ApngWriter::new()`: the frame dimensions, the color mode,
+/// the Bayer CFA pattern and the image export options.
+///
+/// The total number of frames must be known upfront.
+///
+/// Usage
+/// -----
+///
+/// ```
+/// use planetarium::{ApngWriter, Canvas, ImageFormat, SpotShape};
+///
+/// let mut c = Canvas::new(64, 64);
+/// let spot = c.add_spot((10.0, 20.0), SpotShape::default().scale(2.5), 0.8);
+///
+/// let mut buf = Vec::new();
+///
+/// // Encode 10 frames displayed for 40 ms each.
+/// let mut apng = ApngWriter::new(&c, &mut buf, 10, 40, ImageFormat::PngGamma8Bpp).unwrap();
+///
+/// for i in 0..10 {
+///     // Move the spot across the canvas.
+///     c.set_spot_offset(spot, (4.0 * i as f32, 2.0 * i as f32));
+///     c.draw();
+///
+///     apng.write_frame(&c).unwrap();
+/// }
+///
+/// apng.finish().unwrap();
+/// ```
+pub struct ApngWriter<W: Write> {
+    /// PNG image data writer
+    writer: Writer<W>,
+
+    /// Frame image format
+    format: ImageFormat,
+
+    /// Frame PNG sample bit depth
+    depth: BitDepth,
+
+    /// Frame dimensions in pixels
+    dimensions: (u32, u32),
+
+    /// Frame sample data buffer
+    framebuf: Vec<u8>,
+}
+
+impl<W: Write> ApngWriter<W> {
+    /// Creates a new animated PNG writer and writes the animation header
+    /// into `writer`.
+    ///
+    /// The animation consists of `frames` frames displayed for `delay_ms`
+    /// milliseconds each and is played in an infinite loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not a PNG image format.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if the animation parameters
+    /// are invalid, e.g. the number of frames is zero.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn new<P: CanvasPixel>(
+        canvas: &Canvas<P>,
+        writer: W,
+        frames: u32,
+        delay_ms: u16,
+        format: ImageFormat,
+    ) -> Result<Self, EncoderError> {
+        let (depth, gamma) = match format {
+            ImageFormat::PngGamma8Bpp | ImageFormat::PngRgbGamma8Bpp => {
+                // sRGB compression gamma = 1 / 2.2 = 0.45455 (rounded)
+                (BitDepth::Eight, ScaledFloat::from_scaled(45455))
+            }
+            ImageFormat::PngLinear16Bpp | ImageFormat::PngRgbLinear16Bpp => {
+                (BitDepth::Sixteen, ScaledFloat::new(1.0))
+            }
+            _ => return Err(EncoderError::NotImplemented),
+        };
+
+        canvas.validate_color_mode(format)?;
+
+        let (width, height) = canvas.dimensions();
+        let window = Window::new(width, height);
+
+        let mut encoder = Encoder::new(writer, width, height);
+        encoder.set_color(canvas.png_color_type());
+        encoder.set_depth(depth);
+        encoder.set_source_gamma(gamma);
+        encoder.set_animated(frames, 0)?;
+        encoder.set_frame_delay(delay_ms, 1000)?;
+        // Check the number of the written frames.
+        encoder.validate_sequence(true);
+        add_cfa_pattern(&mut encoder, canvas.exported_bayer_pattern(window, (1, 1)))?;
+        canvas.apply_export_options(&mut encoder)?;
+
+        let writer = encoder.write_header()?;
+
+        Ok(ApngWriter {
+            writer,
+            format,
+            depth,
+            dimensions: (width, height),
+            framebuf: Vec::new(),
+        })
+    }
+
+    /// Encodes the canvas image as the next animation frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the canvas dimensions
+    /// do not match the animation frame dimensions.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the animation frame
+    /// image format does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if all frames
+    /// have already been written.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing the frame fails.
+    pub fn write_frame<P: CanvasPixel>(&mut self, canvas: &Canvas<P>) -> Result<(), EncoderError> {
+        if canvas.dimensions() != self.dimensions {
+            return Err(EncoderError::BrokenWindow);
+        }
+
+        canvas.validate_color_mode(self.format)?;
+
+        let (width, height) = self.dimensions;
+        let window = Window::new(width, height);
+
+        self.framebuf.clear();
+
+        if self.depth == BitDepth::Eight {
+            canvas.write_png8bpp_samples(window, &mut self.framebuf)?;
+        } else {
+            canvas.write_png16bpp_samples(window, &mut self.framebuf)?;
+        }
+
+        Ok(self.writer.write_image_data(&self.framebuf)?)
+    }
+
+    /// Finishes the animated PNG image.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if fewer frames
+    /// than requested have been written.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to the underlying writer fails.
+    pub fn finish(self) -> Result<(), EncoderError> {
+        Ok(self.writer.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorMode, SpotShape};
+
+    /// Counts the PNG chunks of the given type.
+    fn count_chunks(img: &[u8], chunk_type: [u8; 4]) -> usize {
+        img.windows(4).filter(|w| *w == chunk_type).count()
+    }
+
+    #[test]
+    fn export_apng_frames() {
+        let mut c = Canvas::new(64, 32);
+        c.set_background(1000);
+        let spot = c.add_spot((10.5, 12.2), SpotShape::default().scale(2.5), 0.8);
+
+        let mut buf = Vec::new();
+        let mut apng = ApngWriter::new(&c, &mut buf, 3, 100, ImageFormat::PngLinear16Bpp).unwrap();
+
+        for dx in [0.0, 10.0, 20.0] {
+            c.set_spot_offset(spot, (dx, 5.0));
+            c.draw();
+
+            apng.write_frame(&c).unwrap();
+        }
+
+        // All requested frames have been written.
+        assert_eq!(apng.write_frame(&c), Err(EncoderError::InvalidParameters));
+        apng.finish().unwrap();
+
+        assert_eq!(count_chunks(&buf, *b"acTL"), 1);
+        assert_eq!(count_chunks(&buf, *b"fcTL"), 3);
+        assert_eq!(count_chunks(&buf, *b"IDAT"), 1);
+        assert_eq!(count_chunks(&buf, *b"fdAT"), 2);
+
+        let mut reader = png::Decoder::new(&buf[..]).read_info().unwrap();
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames, 3);
+
+        let mut frame = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut frame).unwrap();
+
+        // The first frame matches the first canvas image.
+        c.set_spot_offset(spot, (0.0, 5.0));
+        c.draw();
+        let img = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        let mut reader = png::Decoder::new(&img[..]).read_info().unwrap();
+        let mut expected = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut expected).unwrap();
+
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn apng_errors() {
+        let mut c = Canvas::new(16, 16);
+        let mut buf = Vec::new();
+
+        assert!(matches!(
+            ApngWriter::new(&c, &mut buf, 2, 10, ImageFormat::RawGamma8Bpp),
+            Err(EncoderError::NotImplemented)
+        ));
+        assert!(matches!(
+            ApngWriter::new(&c, &mut buf, 2, 10, ImageFormat::PngRgbGamma8Bpp),
+            Err(EncoderError::IncompatibleColorMode)
+        ));
+        assert!(matches!(
+            ApngWriter::new(&c, &mut buf, 0, 10, ImageFormat::PngGamma8Bpp),
+            Err(EncoderError::InvalidParameters)
+        ));
+
+        let mut apng = ApngWriter::new(&c, &mut buf, 2, 10, ImageFormat::PngGamma8Bpp).unwrap();
+
+        assert_eq!(
+            apng.write_frame(&Canvas::new(16, 8)),
+            Err(EncoderError::BrokenWindow)
+        );
+
+        c.set_color_mode(ColorMode::Rgb);
+        assert_eq!(
+            apng.write_frame(&c),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+
+        // Not enough frames written
+        c.set_color_mode(ColorMode::Monochrome);
+        apng.write_frame(&c).unwrap();
+        assert_eq!(apng.finish(), Err(EncoderError::InvalidParameters));
+    }
+}
@@ -0,0 +1,231 @@
+//! Planetarium
+//! ===========
+//!
+//! Private NumPy array export routines
+//! -----------------------------------
+//!
+//! Contains implementations of private methods
+//! for the existing public types.
+
+use std::io::Write;
+
+use super::SampleWriter;
+use crate::{Binning, Canvas, CanvasPixel, EncoderError, Window};
+
+/// NumPy array file magic string and format version 1.0
+const NPY_MAGIC: &[u8; 8] = b"\x93NUMPY\x01\x00";
+
+/// NumPy array file header alignment in bytes
+const NPY_HEADER_ALIGN: usize = 64;
+
+/// NumPy array element data types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum NpyDtype {
+    /// Little-endian unsigned 16-bit integer (`uint16`)
+    U16,
+    /// Little-endian 32-bit floating point (`float32`)
+    F32,
+}
+
+impl NpyDtype {
+    /// Returns the NumPy array type descriptor string.
+    fn descr(self) -> &'static str {
+        match self {
+            NpyDtype::U16 => "<u2",
+            NpyDtype::F32 => "<f4",
+        }
+    }
+
+    /// Encodes the linear light pixel value as the array element.
+    ///
+    /// The floating point values are normalized to the pixel type full scale.
+    fn encode<P: CanvasPixel>(
+        self,
+        p: P,
+        rawbuf: &mut SampleWriter<impl Write>,
+    ) -> std::io::Result<()> {
+        match self {
+            NpyDtype::U16 => rawbuf.write(&p.to_u16().to_le_bytes()),
+            NpyDtype::F32 => {
+                let v = p.to_level() / P::FULL_SCALE;
+                rawbuf.write(&v.to_le_bytes())
+            }
+        }
+    }
+}
+
+/// Writes the NumPy array file header for a C-ordered 2D array
+/// with `(height, width)` dimensions.
+fn write_npy_header<W: Write>(
+    rawbuf: &mut SampleWriter<W>,
+    dtype: NpyDtype,
+    (width, height): (u32, u32),
+) -> std::io::Result<()> {
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+        dtype.descr(),
+        height,
+        width
+    );
+
+    // Pad the header with spaces to align the array data,
+    // the header is terminated by a newline character.
+    let len = NPY_MAGIC.len() + 2 + dict.len() + 1;
+    let padding = len.next_multiple_of(NPY_HEADER_ALIGN) - len;
+    dict.extend(std::iter::repeat_n(' ', padding));
+    dict.push('\n');
+
+    // The header dictionary length is always small enough.
+    let dict_len = u16::try_from(dict.len()).unwrap();
+
+    rawbuf.write(NPY_MAGIC)?;
+    rawbuf.write(&dict_len.to_le_bytes())?;
+    rawbuf.write(dict.as_bytes())
+}
+
+impl<P: CanvasPixel> Canvas<P> {
+    /// Exports the canvas window contents as a linear light grayscale
+    /// NumPy array file with the `dtype` element type.
+    pub(super) fn export_npy<W: Write>(
+        &self,
+        window: Window,
+        dtype: NpyDtype,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        // Buffered writer to encode the array data to
+        let mut rawbuf = SampleWriter::new(writer);
+
+        write_npy_header(&mut rawbuf, dtype, (window.w, window.h))?;
+
+        // The window is bounds checked by the caller.
+        for span in self.window_spans(window).unwrap() {
+            for p in span {
+                dtype.encode(*p, &mut rawbuf)?;
+            }
+        }
+
+        Ok(rawbuf.finish()?)
+    }
+
+    /// Exports the subsampled canvas window contents as a linear light grayscale
+    /// NumPy array file with the `dtype` element type.
+    pub(super) fn export_sub_npy<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+        dtype: NpyDtype,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        // Buffered writer to encode the array data to
+        let mut rawbuf = SampleWriter::new(writer);
+
+        let (width, height) = (window.w / factors.0, window.h / factors.1);
+        write_npy_header(&mut rawbuf, dtype, (width, height))?;
+
+        for i in 0..height {
+            for j in 0..width {
+                let sample = self.subsampled_sample(window, (j, i), 0, factors, binning);
+                dtype.encode(sample, &mut rawbuf)?;
+            }
+        }
+
+        Ok(rawbuf.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ImageFormat, SpotShape};
+
+    use super::*;
+
+    /// Creates a 256x128 canvas image for all tests.
+    fn mkimage() -> Canvas {
+        let mut c = Canvas::new(256, 128);
+        c.set_background(1000);
+
+        c.add_spot((100.6, 50.2), SpotShape::default().scale(4.5), 0.9);
+
+        c.draw();
+        c
+    }
+
+    /// Splits the NumPy array file into the header dictionary and the array data.
+    fn parse_npy(npy: &[u8]) -> (&str, &[u8]) {
+        assert_eq!(npy[..8], NPY_MAGIC[..]);
+
+        let len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + len) % NPY_HEADER_ALIGN, 0);
+
+        let (header, data) = npy[10..].split_at(len);
+        (std::str::from_utf8(header).unwrap(), data)
+    }
+
+    #[test]
+    fn export_npy_u16() {
+        let c = mkimage();
+        let npy = c.export_image(ImageFormat::NpyLinear16Bpp).unwrap();
+        let (header, data) = parse_npy(&npy);
+
+        assert!(
+            header.starts_with("{'descr': '<u2', 'fortran_order': False, 'shape': (128, 256), }")
+        );
+        assert!(header.ends_with(" \n"));
+        assert_eq!(data, c.export_image(ImageFormat::RawLinear16BppLE).unwrap());
+    }
+
+    #[test]
+    fn export_npy_f32() {
+        let c = mkimage();
+        let wnd = Window::new(32, 16).at(90, 40);
+
+        let npy = c
+            .export_window_image(wnd, ImageFormat::NpyLinearFloat)
+            .unwrap();
+        let (header, data) = parse_npy(&npy);
+
+        assert!(header.contains("'descr': '<f4'"));
+        assert!(header.contains("'shape': (16, 32)"));
+        assert_eq!(data.len(), 4 * wnd.len());
+
+        let raw = c
+            .export_window_image(wnd, ImageFormat::RawLinear16BppLE)
+            .unwrap();
+
+        for (v, p) in data.chunks_exact(4).zip(raw.chunks_exact(2)) {
+            let v = f32::from_le_bytes(v.try_into().unwrap());
+            let p = u16::from_le_bytes(p.try_into().unwrap());
+
+            assert!((v * 65535.0 - f32::from(p)).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn export_sub_npy() {
+        let c = mkimage();
+        let npy = c
+            .export_subsampled_image((4, 2), ImageFormat::NpyLinear16Bpp)
+            .unwrap();
+        let (header, data) = parse_npy(&npy);
+
+        assert!(header.contains("'shape': (64, 64)"));
+        assert_eq!(
+            data,
+            c.export_subsampled_image((4, 2), ImageFormat::RawLinear16BppLE)
+                .unwrap()
+        );
+
+        let npy = c
+            .export_binned_image((2, 2), Binning::Average, ImageFormat::NpyLinear16Bpp)
+            .unwrap();
+        let (header, data) = parse_npy(&npy);
+
+        assert!(header.contains("'shape': (64, 128)"));
+        assert_eq!(
+            data,
+            c.export_binned_image((2, 2), Binning::Average, ImageFormat::RawLinear16BppLE)
+                .unwrap()
+        );
+    }
+}
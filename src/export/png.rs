@@ -6,177 +6,426 @@
 //!
 //! This module is gated by the "png" feature.
 //!
-//! Contains implementations of optional private methods
+//! Contains implementations of optional methods
 //! for the existing public types.
 
-use std::io::{Cursor, Write};
+use std::io::{Read, Write};
 
-use png::{BitDepth, ColorType, Encoder, ScaledFloat};
+use png::{
+    AdaptiveFilterType, BitDepth, ColorType, Compression, Decoder, Encoder, EncodingError,
+    FilterType, ScaledFloat,
+};
 
-use crate::{Canvas, EncoderError, Window};
+use crate::truth::CSV_HEADER;
+use crate::{
+    BayerPattern, Binning, Canvas, CanvasPixel, ColorMode, EncoderError, ImageMetadata,
+    PngCompression, PngFilter, SpotInfo, Transform, Window,
+};
 
-/// Initial encoded PNG buffer capacity
-const PNG_BUF_CAPACITY: usize = 0x10000;
+/// PNG text chunk keyword for the Bayer CFA pattern identifier
+const CFA_PATTERN_KEYWORD: &str = "CFA Pattern";
 
-#[allow(clippy::unnecessary_wraps)]
-impl Canvas {
-    /// Exports the canvas window contents in the 8-bit gamma-compressed PNG image format.
-    pub(super) fn export_png8bpp(&self, window: Window) -> Result<Vec<u8>, EncoderError> {
-        // Memory buffer to encode the PNG data to
-        let mut pngbuf: Vec<u8> = Vec::with_capacity(PNG_BUF_CAPACITY);
+/// PNG text chunk keyword for the canvas dimensions
+const DIMENSIONS_KEYWORD: &str = "Canvas Dimensions";
 
-        // Turn `&mut Vec<u8>` into something that implements `std::io::Write`.
-        let cursor = Cursor::new(&mut pngbuf);
+/// PNG text chunk keyword for the background light level
+const BACKGROUND_KEYWORD: &str = "Background Level";
 
-        let mut encoder = Encoder::new(cursor, window.w, window.h);
-        encoder.set_color(ColorType::Grayscale);
-        encoder.set_depth(BitDepth::Eight);
-        // sRGB compression gamma = 1 / 2.2 = 0.45455 (rounded)
-        encoder.set_source_gamma(ScaledFloat::from_scaled(45455));
+/// PNG text chunk keyword for the global brightness level
+const BRIGHTNESS_KEYWORD: &str = "Brightness";
 
-        // FIXME: Do we need error handling here?
-        let mut writer = encoder.write_header().unwrap();
-        let mut stream = writer.stream_writer().unwrap();
+/// PNG text chunk keyword for the view coordinate transformation
+const TRANSFORM_KEYWORD: &str = "View Transform";
 
-        // The window is bounds checked by the caller.
-        let spans = self.window_spans(window).unwrap();
+/// PNG text chunk keyword for the light spot parameters CSV table
+const SPOTS_KEYWORD: &str = "Light Spots";
 
-        for span in spans {
-            // Convert pixels to 8-bit sRGB grayscale sample data.
-            for &p in span {
-                let gray8 = self.gamma_curve.transform(p);
-                stream.write_all(&[gray8]).unwrap();
+impl From<EncodingError> for EncoderError {
+    fn from(err: EncodingError) -> Self {
+        match err {
+            EncodingError::IoError(err) => EncoderError::IoError(err.kind()),
+            // Invalid image parameters, e.g. zero image dimensions
+            EncodingError::Format(_) | EncodingError::Parameter(_) => {
+                EncoderError::InvalidParameters
             }
+            EncodingError::LimitsExceeded => EncoderError::ImageTooLarge,
         }
+    }
+}
 
-        // Both PNG writers must be dropped here to release `pngbuf`.
-        stream.finish().unwrap();
-        writer.finish().unwrap();
+impl From<PngCompression> for Compression {
+    fn from(level: PngCompression) -> Self {
+        match level {
+            PngCompression::Fast => Compression::Fast,
+            PngCompression::Balanced => Compression::Default,
+            PngCompression::Best => Compression::Best,
+        }
+    }
+}
 
-        Ok(pngbuf)
+/// Stores the Bayer CFA pattern identifier in a PNG text chunk
+/// if the exported image is mosaicked.
+pub(super) fn add_cfa_pattern<W: Write>(
+    encoder: &mut Encoder<W>,
+    pattern: Option<BayerPattern>,
+) -> Result<(), EncodingError> {
+    if let Some(pattern) = pattern {
+        encoder.add_text_chunk(CFA_PATTERN_KEYWORD.to_string(), pattern.to_string())?;
     }
 
-    /// Exports the canvas window contents in the 16-bit linear light PNG image format.
-    pub(super) fn export_png16bpp(&self, window: Window) -> Result<Vec<u8>, EncoderError> {
-        // Memory buffer to encode the PNG data to
-        let mut pngbuf: Vec<u8> = Vec::with_capacity(PNG_BUF_CAPACITY);
+    Ok(())
+}
 
-        // Turn `&mut Vec<u8>` into something that implements `std::io::Write`.
-        let cursor = Cursor::new(&mut pngbuf);
+/// Parses a comma separated list of numbers ignoring the square brackets.
+fn parse_numbers<const N: usize>(text: &str) -> Option<[f32; N]> {
+    let mut nums = [0.0; N];
+    let mut fields = text.split(',').map(|f| f.trim_matches(['[', ']', ' ']));
 
-        let mut encoder = Encoder::new(cursor, window.w, window.h);
-        encoder.set_color(ColorType::Grayscale);
-        encoder.set_depth(BitDepth::Sixteen);
-        encoder.set_source_gamma(ScaledFloat::new(1.0));
+    for v in &mut nums {
+        *v = fields.next()?.parse().ok()?;
+    }
+
+    fields.next().is_none().then_some(nums)
+}
+
+impl ImageMetadata {
+    /// Reads the canvas rendering parameters from the PNG image text chunks.
+    ///
+    /// The PNG image must be exported with the `metadata` export option enabled.
+    ///
+    /// Returns `None` if the PNG image header can not be decoded or any
+    /// of the metadata items is missing or malformed.
+    pub fn read_png<R: Read>(reader: R) -> Option<ImageMetadata> {
+        let reader = Decoder::new(reader).read_info().ok()?;
+
+        let info = reader.info();
+
+        // The light spot list is stored in a compressed text chunk.
+        let mut chunks: Vec<(&str, String)> = info
+            .uncompressed_latin1_text
+            .iter()
+            .map(|c| (c.keyword.as_str(), c.text.clone()))
+            .collect();
+
+        for c in &info.compressed_latin1_text {
+            chunks.push((c.keyword.as_str(), c.get_text().ok()?));
+        }
+
+        for c in &info.utf8_text {
+            chunks.push((c.keyword.as_str(), c.get_text().ok()?));
+        }
+
+        Self::from_text_chunks(chunks.iter().map(|(k, t)| (*k, t.as_str())))
+    }
+
+    /// Formats the image metadata items as PNG text chunk `(keyword, text)` pairs.
+    #[must_use]
+    fn text_chunks(&self) -> [(&'static str, String); 5] {
+        let (width, height) = self.dimensions;
+
+        [
+            (DIMENSIONS_KEYWORD, format!("{width}x{height}")),
+            (BACKGROUND_KEYWORD, self.background.to_string()),
+            (BRIGHTNESS_KEYWORD, self.brightness.to_string()),
+            (TRANSFORM_KEYWORD, self.transform.to_string()),
+            (
+                SPOTS_KEYWORD,
+                crate::truth::spots_csv(self.spots.iter().copied()),
+            ),
+        ]
+    }
+
+    /// Parses the image metadata from PNG text chunk `(keyword, text)` pairs.
+    ///
+    /// Returns `None` if any metadata item is missing or malformed.
+    fn from_text_chunks<'a, I>(chunks: I) -> Option<ImageMetadata>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut dimensions = None;
+        let mut background = None;
+        let mut brightness = None;
+        let mut transform = None;
+        let mut spots = None;
+
+        for (keyword, text) in chunks {
+            match keyword {
+                DIMENSIONS_KEYWORD => {
+                    let (w, h) = text.split_once('x')?;
+                    dimensions = Some((w.parse().ok()?, h.parse().ok()?));
+                }
+                BACKGROUND_KEYWORD => background = Some(text.parse().ok()?),
+                BRIGHTNESS_KEYWORD => brightness = Some(text.parse().ok()?),
+                TRANSFORM_KEYWORD => {
+                    let [xx, xy, tx, yx, yy, ty] = parse_numbers(text)?;
+                    transform = Some(Transform::from([[xx, xy, tx], [yx, yy, ty]]));
+                }
+                SPOTS_KEYWORD => {
+                    let mut lines = text.lines();
+
+                    if lines.next()? != CSV_HEADER {
+                        return None;
+                    }
+
+                    let list = lines.map(SpotInfo::parse_csv_line);
+                    spots = Some(list.collect::<Option<_>>()?);
+                }
+                // Skip the unrelated text chunks.
+                _ => {}
+            }
+        }
+
+        Some(ImageMetadata {
+            dimensions: dimensions?,
+            background: background?,
+            brightness: brightness?,
+            transform: transform?,
+            spots: spots?,
+        })
+    }
+}
+
+impl<P: CanvasPixel> Canvas<P> {
+    /// Applies the export options to the PNG encoder.
+    ///
+    /// Sets the compression level and the filter type and stores
+    /// the canvas rendering parameters in PNG text chunks if enabled.
+    pub(super) fn apply_export_options<W: Write>(
+        &self,
+        encoder: &mut Encoder<W>,
+    ) -> Result<(), EncodingError> {
+        let options = self.export_options;
+
+        encoder.set_compression(options.png_compression.into());
+
+        let (filter, adaptive) = match options.png_filter {
+            PngFilter::None => (FilterType::NoFilter, AdaptiveFilterType::NonAdaptive),
+            PngFilter::Sub => (FilterType::Sub, AdaptiveFilterType::NonAdaptive),
+            PngFilter::Up => (FilterType::Up, AdaptiveFilterType::NonAdaptive),
+            PngFilter::Average => (FilterType::Avg, AdaptiveFilterType::NonAdaptive),
+            PngFilter::Paeth => (FilterType::Paeth, AdaptiveFilterType::NonAdaptive),
+            PngFilter::Adaptive => (FilterType::Sub, AdaptiveFilterType::Adaptive),
+        };
+
+        encoder.set_filter(filter);
+        encoder.set_adaptive_filter(adaptive);
+
+        if options.metadata {
+            for (keyword, text) in self.image_metadata().text_chunks() {
+                if keyword == SPOTS_KEYWORD {
+                    // The CSV table grows with the number of light spots.
+                    encoder.add_ztxt_chunk(keyword.to_string(), text)?;
+                } else {
+                    encoder.add_text_chunk(keyword.to_string(), text)?;
+                }
+            }
+        }
 
-        // FIXME: Do we need error handling here?
-        let mut writer = encoder.write_header().unwrap();
-        let mut stream = writer.stream_writer().unwrap();
+        Ok(())
+    }
 
-        // The window is bounds checked by the caller.
-        let spans = self.window_spans(window).unwrap();
+    /// Selects the PNG color type matching the canvas color mode.
+    #[must_use]
+    pub(super) fn png_color_type(&self) -> ColorType {
+        match self.color_mode {
+            ColorMode::Rgb => ColorType::Rgb,
+            _ => ColorType::Grayscale,
+        }
+    }
+
+    /// Writes the canvas window image data as 8-bit gamma-compressed PNG samples.
+    ///
+    /// The window is bounds checked by the caller.
+    pub(super) fn write_png8bpp_samples<W: Write>(
+        &self,
+        window: Window,
+        mut stream: W,
+    ) -> std::io::Result<()> {
+        for span in self.window_spans(window).unwrap() {
+            // Convert pixels to 8-bit sRGB grayscale sample data.
+            for &p in span {
+                let gray8 = self.gamma_curve.transform(p.to_u16());
+                stream.write_all(&[gray8])?;
+            }
+        }
+
+        Ok(())
+    }
 
-        for span in spans {
+    /// Writes the canvas window image data as 16-bit linear light PNG samples.
+    ///
+    /// The window is bounds checked by the caller.
+    pub(super) fn write_png16bpp_samples<W: Write>(
+        &self,
+        window: Window,
+        mut stream: W,
+    ) -> std::io::Result<()> {
+        for span in self.window_spans(window).unwrap() {
             // Convert pixels to 16-bit Big Endian sample data as required
             // by the PNG format specification.
             for p in span {
-                stream.write_all(&p.to_be_bytes()).unwrap();
+                stream.write_all(&p.to_u16().to_be_bytes())?;
             }
         }
 
-        // Both PNG writers must be dropped here to release `pngbuf`.
-        stream.finish().unwrap();
-        writer.finish().unwrap();
+        Ok(())
+    }
+
+    /// Exports the canvas window contents in the 8-bit gamma-compressed PNG image format.
+    pub(super) fn export_png8bpp<W: Write>(
+        &self,
+        window: Window,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        let mut encoder = Encoder::new(writer, window.w, window.h);
+        encoder.set_color(self.png_color_type());
+        encoder.set_depth(BitDepth::Eight);
+        // sRGB compression gamma = 1 / 2.2 = 0.45455 (rounded)
+        encoder.set_source_gamma(ScaledFloat::from_scaled(45455));
+        add_cfa_pattern(&mut encoder, self.exported_bayer_pattern(window, (1, 1)))?;
+        self.apply_export_options(&mut encoder)?;
+
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
+
+        self.write_png8bpp_samples(window, &mut stream)?;
 
-        Ok(pngbuf)
+        // Both PNG writers must be finished here to report the I/O errors.
+        stream.finish()?;
+        writer.finish()?;
+
+        Ok(())
     }
 
-    /// Exports the subsampled canvas contents in the 8-bit gamma-compressed
-    /// PNG image format.
-    pub(super) fn export_sub_png8bpp(&self, factors: (u32, u32)) -> Result<Vec<u8>, EncoderError> {
-        // Memory buffer to encode the PNG data to
-        let mut pngbuf: Vec<u8> = Vec::with_capacity(PNG_BUF_CAPACITY);
+    /// Exports the canvas window contents in the 16-bit linear light PNG image format.
+    pub(super) fn export_png16bpp<W: Write>(
+        &self,
+        window: Window,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        let mut encoder = Encoder::new(writer, window.w, window.h);
+        encoder.set_color(self.png_color_type());
+        encoder.set_depth(BitDepth::Sixteen);
+        encoder.set_source_gamma(ScaledFloat::new(1.0));
+        add_cfa_pattern(&mut encoder, self.exported_bayer_pattern(window, (1, 1)))?;
+        self.apply_export_options(&mut encoder)?;
+
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
+
+        self.write_png16bpp_samples(window, &mut stream)?;
 
+        // Both PNG writers must be finished here to report the I/O errors.
+        stream.finish()?;
+        writer.finish()?;
+
+        Ok(())
+    }
+
+    /// Exports the subsampled canvas window contents in the 8-bit gamma-compressed
+    /// PNG image format.
+    pub(super) fn export_sub_png8bpp<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+        writer: W,
+    ) -> Result<(), EncoderError> {
         // Subsampled image dimensions
-        let width = self.width / factors.0;
-        let height = self.height / factors.1;
+        let width = window.w / factors.0;
+        let height = window.h / factors.1;
 
-        // Turn `&mut Vec<u8>` into something that implements `std::io::Write`.
-        let cursor = Cursor::new(&mut pngbuf);
+        // The binned Bayer mosaic retains the canvas CFA pattern.
+        let pattern = match binning {
+            Some(_) => self.exported_bayer_pattern(window, (1, 1)),
+            None => self.exported_bayer_pattern(window, factors),
+        };
 
-        let mut encoder = Encoder::new(cursor, width, height);
-        encoder.set_color(ColorType::Grayscale);
+        let mut encoder = Encoder::new(writer, width, height);
+        encoder.set_color(self.png_color_type());
         encoder.set_depth(BitDepth::Eight);
         // sRGB compression gamma = 1 / 2.2 = 0.45455 (rounded)
         encoder.set_source_gamma(ScaledFloat::from_scaled(45455));
-
-        // FIXME: Do we need error handling here?
-        let mut writer = encoder.write_header().unwrap();
-        let mut stream = writer.stream_writer().unwrap();
-
-        for i in 0..(self.height / factors.1) {
-            let loffset = (i * factors.1 * self.width) as usize;
-
-            for j in 0..(self.width / factors.0) {
-                let offset = loffset + (j * factors.0) as usize;
-                let gray8 = self.gamma_curve.transform(self.pixbuf[offset]);
-                stream.write_all(&[gray8]).unwrap();
+        add_cfa_pattern(&mut encoder, pattern)?;
+        self.apply_export_options(&mut encoder)?;
+
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
+
+        // Number of interleaved color channel samples per pixel
+        let channels = self.color_mode.channels();
+
+        for i in 0..(window.h / factors.1) {
+            for j in 0..(window.w / factors.0) {
+                for c in 0..channels {
+                    let sample = self.subsampled_sample(window, (j, i), c, factors, binning);
+                    let gray8 = self.gamma_curve.transform(sample);
+                    stream.write_all(&[gray8])?;
+                }
             }
         }
 
-        // Both PNG writers must be dropped here to release `pngbuf`.
-        stream.finish().unwrap();
-        writer.finish().unwrap();
+        // Both PNG writers must be finished here to report the I/O errors.
+        stream.finish()?;
+        writer.finish()?;
 
-        Ok(pngbuf)
+        Ok(())
     }
 
-    /// Exports the subsampled canvas contents in the 16-bit linear light
+    /// Exports the subsampled canvas window contents in the 16-bit linear light
     /// PNG image format.
-    pub(super) fn export_sub_png16bpp(&self, factors: (u32, u32)) -> Result<Vec<u8>, EncoderError> {
-        // Memory buffer to encode the PNG data to
-        let mut pngbuf: Vec<u8> = Vec::with_capacity(PNG_BUF_CAPACITY);
-
+    pub(super) fn export_sub_png16bpp<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+        writer: W,
+    ) -> Result<(), EncoderError> {
         // Subsampled image dimensions
-        let width = self.width / factors.0;
-        let height = self.height / factors.1;
+        let width = window.w / factors.0;
+        let height = window.h / factors.1;
 
-        // Turn `&mut Vec<u8>` into something that implements `std::io::Write`.
-        let cursor = Cursor::new(&mut pngbuf);
+        // The binned Bayer mosaic retains the canvas CFA pattern.
+        let pattern = match binning {
+            Some(_) => self.exported_bayer_pattern(window, (1, 1)),
+            None => self.exported_bayer_pattern(window, factors),
+        };
 
-        let mut encoder = Encoder::new(cursor, width, height);
-        encoder.set_color(ColorType::Grayscale);
+        let mut encoder = Encoder::new(writer, width, height);
+        encoder.set_color(self.png_color_type());
         encoder.set_depth(BitDepth::Sixteen);
         encoder.set_source_gamma(ScaledFloat::new(1.0));
+        add_cfa_pattern(&mut encoder, pattern)?;
+        self.apply_export_options(&mut encoder)?;
 
-        // FIXME: Do we need error handling here?
-        let mut writer = encoder.write_header().unwrap();
-        let mut stream = writer.stream_writer().unwrap();
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
 
-        for i in 0..(self.height / factors.1) {
-            let loffset = (i * factors.1 * self.width) as usize;
-
-            for j in 0..(self.width / factors.0) {
-                let offset = loffset + (j * factors.0) as usize;
+        // Number of interleaved color channel samples per pixel
+        let channels = self.color_mode.channels();
 
+        for i in 0..(window.h / factors.1) {
+            for j in 0..(window.w / factors.0) {
                 // Convert pixels to 16-bit Big Endian sample data as required
                 // by the PNG format specification.
-                let bytes = self.pixbuf[offset].to_be_bytes();
-                stream.write_all(&bytes).unwrap();
+                for c in 0..channels {
+                    let sample = self.subsampled_sample(window, (j, i), c, factors, binning);
+                    stream.write_all(&sample.to_be_bytes())?;
+                }
             }
         }
 
-        // Both PNG writers must be dropped here to release `pngbuf`.
-        stream.finish().unwrap();
-        writer.finish().unwrap();
+        // Both PNG writers must be finished here to report the I/O errors.
+        stream.finish()?;
+        writer.finish()?;
 
-        Ok(pngbuf)
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{ImageFormat, SpotShape};
+    use crate::{ExportOptions, ImageFormat, PngCompression, PngFilter, SpotColor, SpotShape};
 
     use super::*;
 
@@ -242,4 +491,193 @@ mod tests {
             .unwrap();
         assert_eq!(img.len(), 719);
     }
+
+    #[test]
+    fn export_png_to_writer() {
+        let c = mkimage();
+
+        let mut buf = Vec::new();
+        c.export_image_to(ImageFormat::PngLinear16Bpp, &mut buf)
+            .unwrap();
+        assert_eq!(buf, c.export_image(ImageFormat::PngLinear16Bpp).unwrap());
+
+        // The output slice is too short.
+        // Note: The PNG stream writer does not preserve the I/O error kinds.
+        let mut bytes = [0u8; 100];
+        assert!(matches!(
+            c.export_subsampled_image_to((2, 2), ImageFormat::PngGamma8Bpp, &mut bytes[..]),
+            Err(EncoderError::IoError(_))
+        ));
+
+        // Empty PNG images are not allowed.
+        assert_eq!(
+            c.export_window_image(Window::new(0, 0), ImageFormat::PngGamma8Bpp),
+            Err(EncoderError::InvalidParameters)
+        );
+    }
+
+    #[test]
+    fn export_rgb_png() {
+        let mut c = mkimage();
+        c.set_color_mode(ColorMode::Rgb);
+        c.set_spot_color(0, SpotColor::new(1.0, 0.8, 0.5));
+        c.set_spot_color(1, SpotColor::new(0.4, 0.7, 1.0));
+        c.draw();
+
+        // PNG IHDR chunk color type byte offset
+        const COLOR_TYPE_OFFSET: usize = 25;
+
+        let img = c.export_image(ImageFormat::PngRgbGamma8Bpp).unwrap();
+        assert_eq!(img[COLOR_TYPE_OFFSET], ColorType::Rgb as u8);
+        assert_eq!(img[COLOR_TYPE_OFFSET - 1], 8);
+
+        let wnd = Window::new(32, 16).at(90, 140);
+        let img = c
+            .export_window_image(wnd, ImageFormat::PngRgbLinear16Bpp)
+            .unwrap();
+        assert_eq!(img[COLOR_TYPE_OFFSET], ColorType::Rgb as u8);
+        assert_eq!(img[COLOR_TYPE_OFFSET - 1], 16);
+
+        let img = c
+            .export_subsampled_image((2, 2), ImageFormat::PngRgbGamma8Bpp)
+            .unwrap();
+        assert_eq!(img[COLOR_TYPE_OFFSET], ColorType::Rgb as u8);
+    }
+
+    #[test]
+    fn export_bayer_png16bpp() {
+        let mut c = mkimage();
+        c.set_color_mode(ColorMode::Bayer(BayerPattern::Bggr));
+        c.draw();
+
+        let has_cfa_chunk = |img: &[u8], pattern: &str| {
+            let mut chunk = b"tEXtCFA Pattern\0".to_vec();
+            chunk.extend_from_slice(pattern.as_bytes());
+            img.windows(chunk.len()).any(|w| w == chunk)
+        };
+
+        let img = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        assert!(has_cfa_chunk(&img, "BGGR"));
+
+        let wnd = Window::new(32, 16).at(91, 140);
+        let img = c
+            .export_window_image(wnd, ImageFormat::PngGamma8Bpp)
+            .unwrap();
+        assert!(has_cfa_chunk(&img, "GBRG"));
+
+        let img = c
+            .export_subsampled_image((3, 1), ImageFormat::PngLinear16Bpp)
+            .unwrap();
+        assert!(has_cfa_chunk(&img, "BGGR"));
+
+        let img = c
+            .export_subsampled_image((2, 2), ImageFormat::PngLinear16Bpp)
+            .unwrap();
+        assert!(!has_cfa_chunk(&img, "BGGR"));
+
+        // The binned Bayer mosaic retains the CFA pattern.
+        let img = c
+            .export_binned_image((2, 2), Binning::Average, ImageFormat::PngLinear16Bpp)
+            .unwrap();
+        assert!(has_cfa_chunk(&img, "BGGR"));
+    }
+
+    #[test]
+    fn export_png_metadata() {
+        let mut c = mkimage();
+        c.set_view_transform(Transform::default().translate((1.5, -2.0)));
+        c.set_brightness(0.8);
+
+        // The metadata chunks are disabled by default.
+        let img = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        assert!(ImageMetadata::read_png(&img[..]).is_none());
+
+        c.set_export_options(ExportOptions {
+            metadata: true,
+            ..Default::default()
+        });
+
+        let wnd = Window::new(32, 16).at(90, 140);
+        let img = c
+            .export_window_image(wnd, ImageFormat::PngLinear16Bpp)
+            .unwrap();
+        let meta = ImageMetadata::read_png(&img[..]).unwrap();
+
+        // The light spot list is stored compressed.
+        assert_eq!(img.windows(4).filter(|w| w == b"zTXt").count(), 1);
+        assert!(!img
+            .windows(CSV_HEADER.len())
+            .any(|w| w == CSV_HEADER.as_bytes()));
+
+        assert_eq!(meta.dimensions, (256, 256));
+        assert_eq!(meta.background, 1000);
+        assert_eq!(meta.brightness, 0.8);
+        assert_eq!(meta.transform.to_string(), "[[1, 0, 1.5], [0, 1, -2]]");
+        assert_eq!(meta.spots.len(), 2);
+
+        for s in &meta.spots {
+            let info = c.spot_info(s.id).unwrap();
+
+            assert_eq!(s.position, info.position);
+            assert_eq!(s.intensity, info.intensity);
+            assert_eq!(s.shape.to_string(), info.shape.to_string());
+            assert_eq!(s.radius, info.radius);
+            assert!(s.visible);
+        }
+
+        let img = c
+            .export_subsampled_image((2, 2), ImageFormat::PngGamma8Bpp)
+            .unwrap();
+        assert!(ImageMetadata::read_png(&img[..]).is_some());
+
+        // Malformed metadata items
+        let broken = [(BRIGHTNESS_KEYWORD, "bright")];
+        assert!(ImageMetadata::from_text_chunks(broken).is_none());
+
+        // Not a PNG image
+        assert!(ImageMetadata::read_png(&b"GIF89a"[..]).is_none());
+    }
+
+    #[test]
+    fn export_png_compression() {
+        let mut c = mkimage();
+
+        let default = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        assert_eq!(default.len(), 2561);
+
+        c.set_export_options(ExportOptions {
+            png_compression: PngCompression::Balanced,
+            png_filter: PngFilter::Paeth,
+            ..Default::default()
+        });
+        let balanced = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        assert!(balanced.len() < default.len());
+
+        c.set_export_options(ExportOptions {
+            png_compression: PngCompression::Best,
+            png_filter: PngFilter::Adaptive,
+            ..Default::default()
+        });
+        let best = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        assert!(best.len() < default.len());
+
+        c.set_export_options(ExportOptions {
+            png_filter: PngFilter::None,
+            ..Default::default()
+        });
+        let unfiltered = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+        assert_ne!(unfiltered, default);
+
+        // The image data must decode to the same pixels.
+        let decode = |img: &[u8]| {
+            let mut reader = png::Decoder::new(img).read_info().unwrap();
+            let mut buf = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut buf).unwrap();
+            buf
+        };
+
+        assert_eq!(decode(&balanced), decode(&default));
+        assert_eq!(decode(&best), decode(&default));
+        assert_eq!(decode(&unfiltered), decode(&default));
+    }
 }
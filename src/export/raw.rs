@@ -7,98 +7,106 @@
 //! Contains implementations of private methods
 //! for the existing public types.
 
-use crate::{Canvas, EncoderError, Window};
+use std::io::Write;
 
-#[allow(clippy::unnecessary_wraps)]
-impl Canvas {
+use super::SampleWriter;
+use crate::{Binning, Canvas, CanvasPixel, EncoderError, Window};
+
+impl<P: CanvasPixel> Canvas<P> {
     /// Exports the canvas window contents in the 8-bit gamma-compressed RAW image format.
-    pub(super) fn export_raw8bpp(&self, window: Window) -> Result<Vec<u8>, EncoderError> {
-        // Memory buffer to encode the RAW pixel data to
-        let mut rawbuf: Vec<u8> = Vec::with_capacity(window.len());
+    pub(super) fn export_raw8bpp<W: Write>(
+        &self,
+        window: Window,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        // Buffered writer to encode the RAW pixel data to
+        let mut rawbuf = SampleWriter::new(writer);
 
         // The window is bounds checked by the caller.
         for span in self.window_spans(window).unwrap() {
-            rawbuf.extend(span.iter().map(|p| self.gamma_curve.transform(*p)));
+            for p in span {
+                rawbuf.write(&[self.gamma_curve.transform(p.to_u16())])?;
+            }
         }
 
-        Ok(rawbuf)
+        Ok(rawbuf.finish()?)
     }
 
     /// Exports the canvas window contents in the `X`-bit linear light grayscale
     /// little-endian RAW image format.
     ///
     /// The const generic `X` must be in the range from 9 to 16.
-    pub(super) fn export_raw1xbpp<const X: u16>(
+    pub(super) fn export_raw1xbpp<const X: u16, W: Write>(
         &self,
         window: Window,
-    ) -> Result<Vec<u8>, EncoderError> {
-        // Memory buffer to encode the RAW pixel data to
-        let mut rawbuf: Vec<u8> = Vec::with_capacity(2 * window.len());
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        // Buffered writer to encode the RAW pixel data to
+        let mut rawbuf = SampleWriter::new(writer);
 
         // The window is bounds checked by the caller.
         for span in self.window_spans(window).unwrap() {
             for p in span {
-                let bytes = (p >> (16 - X)).to_le_bytes();
-                rawbuf.extend_from_slice(&bytes);
+                let bytes = (p.to_u16() >> (16 - X)).to_le_bytes();
+                rawbuf.write(&bytes)?;
             }
         }
 
-        Ok(rawbuf)
+        Ok(rawbuf.finish()?)
     }
 
-    /// Exports the subsampled canvas contents in the 8-bit gamma-compressed
+    /// Exports the subsampled canvas window contents in the 8-bit gamma-compressed
     /// RAW image format.
-    pub(super) fn export_sub_raw8bpp(&self, factors: (u32, u32)) -> Result<Vec<u8>, EncoderError> {
-        // Subsampled image size in pixels
-        let pixlen = self.pixbuf.len() / (factors.0 * factors.1) as usize;
-
-        // Memory buffer to encode the RAW pixel data to
-        let mut rawbuf: Vec<u8> = Vec::with_capacity(pixlen);
-
-        for i in 0..(self.height / factors.1) {
-            let loffset = (i * factors.1 * self.width) as usize;
-
-            for j in 0..(self.width / factors.0) {
-                let offset = loffset + (j * factors.0) as usize;
-                let xval = self.gamma_curve.transform(self.pixbuf[offset]);
-                rawbuf.push(xval);
+    pub(super) fn export_sub_raw8bpp<W: Write>(
+        &self,
+        window: Window,
+        factors: (u32, u32),
+        binning: Option<Binning>,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        // Buffered writer to encode the RAW pixel data to
+        let mut rawbuf = SampleWriter::new(writer);
+
+        for i in 0..(window.h / factors.1) {
+            for j in 0..(window.w / factors.0) {
+                let sample = self.subsampled_sample(window, (j, i), 0, factors, binning);
+                let xval = self.gamma_curve.transform(sample);
+                rawbuf.write(&[xval])?;
             }
         }
 
-        Ok(rawbuf)
+        Ok(rawbuf.finish()?)
     }
 
-    /// Exports the subsampled canvas contents in the `X`-bit linear light grayscale
+    /// Exports the subsampled canvas window contents in the `X`-bit linear light grayscale
     /// little-endian RAW image format.
     ///
     /// The const generic `X` must be in the range from 9 to 16.
-    pub(super) fn export_sub_raw1xbpp<const X: u16>(
+    pub(super) fn export_sub_raw1xbpp<const X: u16, W: Write>(
         &self,
+        window: Window,
         factors: (u32, u32),
-    ) -> Result<Vec<u8>, EncoderError> {
-        // Subsampled image size in pixels
-        let pixlen = self.pixbuf.len() / (factors.0 * factors.1) as usize;
-
-        // Memory buffer to encode the RAW pixel data to
-        let mut rawbuf: Vec<u8> = Vec::with_capacity(2 * pixlen);
-
-        for i in 0..(self.height / factors.1) {
-            let loffset = (i * factors.1 * self.width) as usize;
-
-            for j in 0..(self.width / factors.0) {
-                let offset = loffset + (j * factors.0) as usize;
-                let bytes = (self.pixbuf[offset] >> (16 - X)).to_le_bytes();
-                rawbuf.extend_from_slice(&bytes);
+        binning: Option<Binning>,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        // Buffered writer to encode the RAW pixel data to
+        let mut rawbuf = SampleWriter::new(writer);
+
+        for i in 0..(window.h / factors.1) {
+            for j in 0..(window.w / factors.0) {
+                let sample = self.subsampled_sample(window, (j, i), 0, factors, binning);
+                let bytes = (sample >> (16 - X)).to_le_bytes();
+                rawbuf.write(&bytes)?;
             }
         }
 
-        Ok(rawbuf)
+        Ok(rawbuf.finish()?)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{ImageFormat, SpotShape};
+    use crate::{CanvasPixel, ImageFormat, SpotShape};
 
     use super::*;
 
@@ -194,6 +202,99 @@ mod tests {
         assert_eq!(img[2 * (150 * 256 + 100) + 1], 13);
     }
 
+    #[test]
+    fn export_raw16bpp() {
+        let c = mkimage();
+        let img = c.export_image(ImageFormat::RawLinear16BppLE).unwrap();
+        assert_eq!(img.len(), 256 * 256 * 2);
+        assert_eq!(img[0], 0xE8);
+        assert_eq!(img[1], 0x03);
+
+        let offset = 150 * 256 + 100;
+        let bytes = c.pixels()[offset].to_le_bytes();
+        assert_eq!(img[2 * offset..2 * offset + 2], bytes);
+    }
+
+    #[test]
+    fn export_generic_raw16bpp() {
+        let mut c = Canvas::<f32>::with_dimensions(64, 64);
+        c.set_background(0.25);
+        c.add_spot((20.6, 30.2), SpotShape::default().scale(4.5), 0.9);
+        c.draw();
+
+        let img = c.export_image(ImageFormat::RawLinear16BppLE).unwrap();
+        assert_eq!(img.len(), 64 * 64 * 2);
+        assert_eq!(img[..2], 16383u16.to_le_bytes());
+
+        let offset = 30 * 64 + 20;
+        let bytes = c.pixels()[offset].to_u16().to_le_bytes();
+        assert_eq!(img[2 * offset..2 * offset + 2], bytes);
+    }
+
+    #[test]
+    fn export_raw_to_writer() {
+        let c = mkimage();
+
+        let mut buf = Vec::new();
+        c.export_image_to(ImageFormat::RawLinear12BppLE, &mut buf)
+            .unwrap();
+        assert_eq!(buf, c.export_image(ImageFormat::RawLinear12BppLE).unwrap());
+
+        let wnd = Window::new(32, 16).at(90, 140);
+
+        let mut buf = Vec::new();
+        c.export_window_image_to(wnd, ImageFormat::RawGamma8Bpp, &mut buf)
+            .unwrap();
+        assert_eq!(
+            buf,
+            c.export_window_image(wnd, ImageFormat::RawGamma8Bpp)
+                .unwrap()
+        );
+
+        // The output slice is too short.
+        let mut bytes = [0u8; 1000];
+        assert_eq!(
+            c.export_subsampled_image_to((2, 2), ImageFormat::RawGamma8Bpp, &mut bytes[..]),
+            Err(EncoderError::IoError(std::io::ErrorKind::WriteZero))
+        );
+    }
+
+    #[test]
+    fn export_raw_into_buffer() {
+        let c = mkimage();
+
+        // Reuse the same output buffer for all exports.
+        let mut buf = vec![0u8; 256 * 256 * 2];
+
+        let len = c
+            .export_image_into(ImageFormat::RawLinear10BppLE, &mut buf)
+            .unwrap();
+        assert_eq!(len, 256 * 256 * 2);
+        assert_eq!(buf, c.export_image(ImageFormat::RawLinear10BppLE).unwrap());
+
+        let wnd = Window::new(32, 16).at(90, 140);
+
+        let len = c
+            .export_window_image_into(wnd, ImageFormat::RawGamma8Bpp, &mut buf)
+            .unwrap();
+        assert_eq!(len, wnd.len());
+        assert_eq!(
+            buf[..len],
+            c.export_window_image(wnd, ImageFormat::RawGamma8Bpp)
+                .unwrap()
+        );
+
+        let len = c
+            .export_subsampled_image_into((4, 2), ImageFormat::RawLinear12BppLE, &mut buf)
+            .unwrap();
+        assert_eq!(len, 256 * 256 * 2 / 4 / 2);
+        assert_eq!(
+            buf[..len],
+            c.export_subsampled_image((4, 2), ImageFormat::RawLinear12BppLE)
+                .unwrap()
+        );
+    }
+
     #[test]
     fn export_sub_raw12bpp() {
         let img = mkimage()
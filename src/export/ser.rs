@@ -0,0 +1,391 @@
+//! Planetarium
+//! ===========
+//!
+//! SER video file export routines
+//! ------------------------------
+//!
+//! Defines the public SER astronomy video file writer `SerWriter`.
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{BayerPattern, Canvas, CanvasPixel, ColorMode, EncoderError, ImageFormat, Window};
+
+/// SER file header identifier string
+const SER_FILE_ID: &[u8; 14] = b"LUCAM-RECORDER";
+
+/// SER file header instrument name
+const SER_INSTRUMENT: &str = "Planetarium";
+
+/// SER file header text field length
+const SER_TEXT_LEN: usize = 40;
+
+/// SER timestamp value of the Unix epoch (1970-01-01T00:00:00Z)
+///
+/// The SER timestamps count 100 ns ticks since 0001-01-01T00:00:00Z.
+const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+
+/// Converts the system time into a SER timestamp value.
+fn ser_timestamp(time: SystemTime) -> i64 {
+    let ticks = |d: std::time::Duration| i64::try_from(d.as_nanos() / 100).unwrap_or(i64::MAX);
+
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => UNIX_EPOCH_TICKS.saturating_add(ticks(d)),
+        Err(e) => UNIX_EPOCH_TICKS.saturating_sub(ticks(e.duration())),
+    }
+}
+
+/// Returns the SER color identifier for the canvas color mode.
+fn ser_color_id(mode: ColorMode) -> i32 {
+    match mode {
+        ColorMode::Bayer(BayerPattern::Rggb) => 8,
+        ColorMode::Bayer(BayerPattern::Grbg) => 9,
+        ColorMode::Bayer(BayerPattern::Gbrg) => 10,
+        ColorMode::Bayer(BayerPattern::Bggr) => 11,
+        // Monochrome
+        _ => 0,
+    }
+}
+
+/// SER astronomy video file writer
+///
+/// Encodes the successive canvas images as SER video frames
+/// with per-frame UTC timestamps. The SER file header settings are taken
+/// from the canvas passed to `SerWriter::new()`: the frame dimensions
+/// and the color mode (monochrome or Bayer mosaic).
+///
+/// The total number of frames must be known upfront.
+///
+/// The supported frame image formats are `RawGamma8Bpp` (8-bit samples)
+/// and `RawLinear10BppLE`, `RawLinear12BppLE`, `RawLinear16BppLE`
+/// (16-bit samples with 10, 12 or 16 significant bits).
+///
+/// Note: The 16-bit samples are stored in the little-endian byte order
+/// with the `LittleEndian` header field set to 0 following the common
+/// SER capture software convention.
+///
+/// Usage
+/// -----
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use planetarium::{Canvas, ImageFormat, SerWriter, SpotShape};
+///
+/// let mut c = Canvas::new(64, 64);
+/// let spot = c.add_spot((10.0, 20.0), SpotShape::default().scale(2.5), 0.8);
+///
+/// let mut buf = Vec::new();
+/// let mut ser = SerWriter::new(&c, &mut buf, 10, ImageFormat::RawLinear12BppLE).unwrap();
+///
+/// let start = SystemTime::now();
+///
+/// for i in 0..10 {
+///     // Move the spot across the canvas.
+///     c.set_spot_offset(spot, (4.0 * i as f32, 2.0 * i as f32));
+///     c.draw();
+///
+///     // Emulate the 50 FPS frame rate.
+///     ser.write_frame(&c, start + Duration::from_millis(20 * i)).unwrap();
+/// }
+///
+/// ser.finish().unwrap();
+/// ```
+pub struct SerWriter<W: Write> {
+    /// Underlying SER file data writer
+    writer: W,
+
+    /// Frame image format
+    format: ImageFormat,
+
+    /// Frame color mode
+    color_mode: ColorMode,
+
+    /// Frame dimensions in pixels
+    dimensions: (u32, u32),
+
+    /// Total number of frames
+    frames: u32,
+
+    /// Written frame timestamps
+    timestamps: Vec<i64>,
+}
+
+impl<W: Write> SerWriter<W> {
+    /// Creates a new SER video file writer for `frames` frames.
+    ///
+    /// The SER file header is written with the first frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::NotImplemented`] if the requested image format
+    /// is not supported by the SER file format.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the requested image format
+    /// does not match the canvas color mode.
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if the number of frames is zero
+    /// or the canvas dimensions are too large.
+    pub fn new<P: CanvasPixel>(
+        canvas: &Canvas<P>,
+        writer: W,
+        frames: u32,
+        format: ImageFormat,
+    ) -> Result<Self, EncoderError> {
+        if Self::pixel_depth(format).is_none() {
+            return Err(EncoderError::NotImplemented);
+        }
+
+        canvas.validate_color_mode(format)?;
+
+        let (width, height) = canvas.dimensions();
+
+        if frames == 0 || i32::try_from(width.max(height).max(frames)).is_err() {
+            return Err(EncoderError::InvalidParameters);
+        }
+
+        Ok(SerWriter {
+            writer,
+            format,
+            color_mode: canvas.color_mode(),
+            dimensions: (width, height),
+            frames,
+            timestamps: Vec::with_capacity(frames as usize),
+        })
+    }
+
+    /// Returns the SER pixel depth per plane for the frame image format.
+    fn pixel_depth(format: ImageFormat) -> Option<i32> {
+        match format {
+            ImageFormat::RawGamma8Bpp => Some(8),
+            ImageFormat::RawLinear10BppLE => Some(10),
+            ImageFormat::RawLinear12BppLE => Some(12),
+            ImageFormat::RawLinear16BppLE => Some(16),
+            _ => None,
+        }
+    }
+
+    /// Writes the SER file header.
+    ///
+    /// The header date fields are set to the first frame timestamp.
+    fn write_header(&mut self, timestamp: i64) -> std::io::Result<()> {
+        // The header field values are range checked in `new()`.
+        let (width, height) = self.dimensions;
+        let fields = [
+            // LuID
+            0,
+            ser_color_id(self.color_mode),
+            // LittleEndian
+            0,
+            i32::try_from(width).unwrap(),
+            i32::try_from(height).unwrap(),
+            Self::pixel_depth(self.format).unwrap(),
+            i32::try_from(self.frames).unwrap(),
+        ];
+
+        let mut header = Vec::with_capacity(178);
+        header.extend_from_slice(SER_FILE_ID);
+
+        for v in fields {
+            header.extend_from_slice(&v.to_le_bytes());
+        }
+
+        // Observer, Instrument and Telescope text fields
+        let mut texts = [[0u8; SER_TEXT_LEN]; 3];
+        texts[1][..SER_INSTRUMENT.len()].copy_from_slice(SER_INSTRUMENT.as_bytes());
+        header.extend(texts.iter().flatten());
+
+        // DateTime and DateTime_UTC
+        header.extend_from_slice(&timestamp.to_le_bytes());
+        header.extend_from_slice(&timestamp.to_le_bytes());
+
+        self.writer.write_all(&header)
+    }
+
+    /// Encodes the canvas image as the next video frame.
+    ///
+    /// The frame `timestamp` is stored in the SER file trailer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the canvas dimensions
+    /// do not match the video frame dimensions.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the canvas color mode
+    /// does not match the video frame color mode.
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if all frames
+    /// have already been written.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing the frame fails.
+    pub fn write_frame<P: CanvasPixel>(
+        &mut self,
+        canvas: &Canvas<P>,
+        timestamp: SystemTime,
+    ) -> Result<(), EncoderError> {
+        if canvas.dimensions() != self.dimensions {
+            return Err(EncoderError::BrokenWindow);
+        }
+
+        if canvas.color_mode() != self.color_mode {
+            return Err(EncoderError::IncompatibleColorMode);
+        }
+
+        if self.timestamps.len() == self.frames as usize {
+            return Err(EncoderError::InvalidParameters);
+        }
+
+        let timestamp = ser_timestamp(timestamp);
+
+        if self.timestamps.is_empty() {
+            self.write_header(timestamp)?;
+        }
+
+        let (width, height) = self.dimensions;
+        let window = Window::new(width, height);
+
+        canvas.encode_window_image(window, self.format, &mut self.writer)?;
+        self.timestamps.push(timestamp);
+
+        Ok(())
+    }
+
+    /// Finishes the SER video file by writing the frame timestamps trailer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if fewer frames
+    /// than requested have been written.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to the underlying writer fails.
+    pub fn finish(mut self) -> Result<(), EncoderError> {
+        if self.timestamps.len() != self.frames as usize {
+            return Err(EncoderError::InvalidParameters);
+        }
+
+        for t in &self.timestamps {
+            self.writer.write_all(&t.to_le_bytes())?;
+        }
+
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::SpotShape;
+
+    /// SER file header size in bytes
+    const HEADER_LEN: usize = 178;
+
+    /// Reads a little-endian 32-bit header field.
+    fn field(ser: &[u8], offset: usize) -> i32 {
+        i32::from_le_bytes(ser[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn convert_timestamps() {
+        assert_eq!(ser_timestamp(UNIX_EPOCH), UNIX_EPOCH_TICKS);
+
+        let t = UNIX_EPOCH + Duration::from_micros(1_500_000);
+        assert_eq!(ser_timestamp(t), UNIX_EPOCH_TICKS + 15_000_000);
+
+        let t = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(ser_timestamp(t), UNIX_EPOCH_TICKS - 10_000_000);
+    }
+
+    #[test]
+    fn export_ser_frames() {
+        let mut c = Canvas::new(32, 16);
+        c.set_background(1000);
+        c.set_color_mode(ColorMode::Bayer(BayerPattern::Gbrg));
+        let spot = c.add_spot((10.5, 8.2), SpotShape::default().scale(2.5), 0.8);
+
+        let fmt = ImageFormat::RawLinear16BppLE;
+        let mut buf = Vec::new();
+        let mut ser = SerWriter::new(&c, &mut buf, 2, fmt).unwrap();
+
+        let t0 = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut frames = Vec::new();
+
+        for (i, dx) in (0..2).zip([0.0, 5.0]) {
+            c.set_spot_offset(spot, (dx, 0.0));
+            c.draw();
+
+            ser.write_frame(&c, t0 + Duration::from_millis(10 * i))
+                .unwrap();
+            frames.extend(c.export_image(fmt).unwrap());
+        }
+
+        ser.finish().unwrap();
+
+        assert_eq!(buf.len(), HEADER_LEN + 2 * 32 * 16 * 2 + 2 * 8);
+        assert_eq!(&buf[..14], SER_FILE_ID);
+
+        // ColorID, LittleEndian, ImageWidth, ImageHeight, PixelDepthPerPlane, FrameCount
+        assert_eq!(field(&buf, 18), 10);
+        assert_eq!(field(&buf, 22), 0);
+        assert_eq!(field(&buf, 26), 32);
+        assert_eq!(field(&buf, 30), 16);
+        assert_eq!(field(&buf, 34), 16);
+        assert_eq!(field(&buf, 38), 2);
+        assert_eq!(&buf[82..93], SER_INSTRUMENT.as_bytes());
+
+        let t0 = ser_timestamp(t0);
+        assert_eq!(buf[162..170], t0.to_le_bytes());
+        assert_eq!(buf[170..178], t0.to_le_bytes());
+
+        let (data, trailer) = buf[HEADER_LEN..].split_at(frames.len());
+        assert_eq!(data, frames);
+
+        // The light spot moves between the frames.
+        let (frame0, frame1) = data.split_at(32 * 16 * 2);
+        assert_ne!(frame0, frame1);
+        assert_eq!(trailer[..8], t0.to_le_bytes());
+        assert_eq!(trailer[8..], (t0 + 100_000).to_le_bytes());
+    }
+
+    #[test]
+    fn ser_errors() {
+        let mut c = Canvas::new(16, 16);
+        let mut buf = Vec::new();
+
+        assert!(matches!(
+            SerWriter::new(&c, &mut buf, 2, ImageFormat::PngGamma8Bpp),
+            Err(EncoderError::NotImplemented)
+        ));
+        assert!(matches!(
+            SerWriter::new(&c, &mut buf, 0, ImageFormat::RawGamma8Bpp),
+            Err(EncoderError::InvalidParameters)
+        ));
+
+        let mut ser = SerWriter::new(&c, &mut buf, 1, ImageFormat::RawGamma8Bpp).unwrap();
+        let now = SystemTime::now();
+
+        assert_eq!(
+            ser.write_frame(&Canvas::new(8, 16), now),
+            Err(EncoderError::BrokenWindow)
+        );
+
+        c.set_color_mode(ColorMode::Bayer(BayerPattern::Rggb));
+        assert_eq!(
+            ser.write_frame(&c, now),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+
+        c.set_color_mode(ColorMode::Monochrome);
+        ser.write_frame(&c, now).unwrap();
+        assert_eq!(
+            ser.write_frame(&c, now),
+            Err(EncoderError::InvalidParameters)
+        );
+        ser.finish().unwrap();
+
+        // Not enough frames written
+        let ser = SerWriter::new(&c, &mut buf, 1, ImageFormat::RawGamma8Bpp).unwrap();
+        assert_eq!(ser.finish(), Err(EncoderError::InvalidParameters));
+    }
+}
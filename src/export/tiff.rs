@@ -0,0 +1,238 @@
+//! Planetarium
+//! ===========
+//!
+//! Private TIFF image export routines
+//! ----------------------------------
+//!
+//! Contains implementations of private methods
+//! for the existing public types.
+
+use std::io::Write;
+
+use super::SampleWriter;
+use crate::{Canvas, CanvasPixel, EncoderError, Window};
+
+/// TIFF field type SHORT (16-bit unsigned integer)
+const TIFF_SHORT: u16 = 3;
+
+/// TIFF field type LONG (32-bit unsigned integer)
+const TIFF_LONG: u16 = 4;
+
+/// Number of the TIFF image file directory entries
+const TIFF_IFD_ENTRIES: u16 = 11;
+
+/// TIFF image data offset (file header, image file directory, alignment)
+const TIFF_DATA_OFFSET: u32 = 8 + (2 + 12 * TIFF_IFD_ENTRIES as u32 + 4) + 2;
+
+/// Writes a TIFF image file directory entry with a single value.
+fn write_ifd_entry<W: Write>(
+    rawbuf: &mut SampleWriter<W>,
+    tag: u16,
+    field_type: u16,
+    value: u32,
+) -> std::io::Result<()> {
+    rawbuf.write(&tag.to_le_bytes())?;
+    rawbuf.write(&field_type.to_le_bytes())?;
+    rawbuf.write(&1u32.to_le_bytes())?;
+
+    // The SHORT values are left-justified in the 4-byte value field.
+    if field_type == TIFF_SHORT {
+        let value = u16::try_from(value).unwrap_or(u16::MAX);
+        rawbuf.write(&value.to_le_bytes())?;
+        rawbuf.write(&[0, 0])
+    } else {
+        rawbuf.write(&value.to_le_bytes())
+    }
+}
+
+impl<P: CanvasPixel> Canvas<P> {
+    /// Exports the canvas window contents in the 32-bit floating point
+    /// linear light grayscale little-endian TIFF image format.
+    ///
+    /// Uses the HDR image buffer samples if the HDR rendering is enabled,
+    /// otherwise the normalized pixel buffer samples.
+    pub(super) fn export_tiff_float<W: Write>(
+        &self,
+        window: Window,
+        writer: W,
+    ) -> Result<(), EncoderError> {
+        let strip_len = u32::try_from(4 * window.len()).map_err(|_| EncoderError::ImageTooLarge)?;
+
+        // Buffered writer to encode the TIFF image data to
+        let mut rawbuf = SampleWriter::new(writer);
+
+        // Little-endian TIFF file header with the first IFD offset
+        rawbuf.write(b"II\x2A\x00")?;
+        rawbuf.write(&8u32.to_le_bytes())?;
+
+        // Image file directory entries sorted by the tag value
+        let entries = [
+            // ImageWidth
+            (256, TIFF_LONG, window.w),
+            // ImageLength
+            (257, TIFF_LONG, window.h),
+            // BitsPerSample
+            (258, TIFF_SHORT, 32),
+            // Compression: none
+            (259, TIFF_SHORT, 1),
+            // PhotometricInterpretation: BlackIsZero
+            (262, TIFF_SHORT, 1),
+            // StripOffsets
+            (273, TIFF_LONG, TIFF_DATA_OFFSET),
+            // SamplesPerPixel
+            (277, TIFF_SHORT, 1),
+            // RowsPerStrip
+            (278, TIFF_LONG, window.h),
+            // StripByteCounts
+            (279, TIFF_LONG, strip_len),
+            // PlanarConfiguration: chunky
+            (284, TIFF_SHORT, 1),
+            // SampleFormat: IEEE floating point
+            (339, TIFF_SHORT, 3),
+        ];
+
+        rawbuf.write(&TIFF_IFD_ENTRIES.to_le_bytes())?;
+
+        for (tag, field_type, value) in entries {
+            write_ifd_entry(&mut rawbuf, tag, field_type, value)?;
+        }
+
+        // No next IFD, align the image data to 4 bytes.
+        rawbuf.write(&0u32.to_le_bytes())?;
+        rawbuf.write(&[0, 0])?;
+
+        // The window is bounds checked by the caller.
+        if let Some(hdrbuf) = self.hdr_pixels() {
+            for i in window.y..(window.y + window.h) {
+                let base = i as usize * self.stride + window.x as usize;
+
+                for v in &hdrbuf[base..base + window.w as usize] {
+                    rawbuf.write(&v.to_le_bytes())?;
+                }
+            }
+        } else {
+            for span in self.window_spans(window).unwrap() {
+                for p in span {
+                    let v = p.to_level() / P::FULL_SCALE;
+                    rawbuf.write(&v.to_le_bytes())?;
+                }
+            }
+        }
+
+        Ok(rawbuf.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ImageFormat, SpotShape};
+
+    use super::*;
+
+    /// Creates a 64x32 canvas image with an overexposed spot.
+    fn mkimage(hdr: bool) -> Canvas {
+        let mut c = Canvas::new(64, 32);
+        c.set_background(1000);
+        c.set_hdr_rendering(hdr);
+
+        c.add_spot((20.0, 10.0), SpotShape::default().scale(2.5), 4.0);
+
+        c.draw();
+        c
+    }
+
+    /// Reads a little-endian 32-bit value at the byte offset.
+    fn read_u32(img: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(img[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Returns the image data sample values.
+    fn samples(img: &[u8]) -> Vec<f32> {
+        img[TIFF_DATA_OFFSET as usize..]
+            .chunks_exact(4)
+            .map(|v| f32::from_le_bytes(v.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn export_tiff_header() {
+        let img = mkimage(false)
+            .export_image(ImageFormat::TiffLinearFloat)
+            .unwrap();
+
+        assert_eq!(img.len(), TIFF_DATA_OFFSET as usize + 4 * 64 * 32);
+        assert_eq!(img[..4], *b"II\x2A\x00");
+        assert_eq!(read_u32(&img, 4), 8);
+        assert_eq!(u16::from_le_bytes([img[8], img[9]]), TIFF_IFD_ENTRIES);
+
+        // ImageWidth and ImageLength
+        assert_eq!(img[10..14], [0x00, 0x01, 0x04, 0x00]);
+        assert_eq!(read_u32(&img, 18), 64);
+        assert_eq!(read_u32(&img, 30), 32);
+
+        // StripOffsets and StripByteCounts
+        assert_eq!(read_u32(&img, 10 + 5 * 12 + 8), TIFF_DATA_OFFSET);
+        assert_eq!(read_u32(&img, 10 + 8 * 12 + 8), 4 * 64 * 32);
+
+        // SampleFormat
+        assert_eq!(img[10 + 10 * 12..10 + 10 * 12 + 2], 339u16.to_le_bytes());
+
+        // The single image strip does not fit into 4 GiB.
+        let huge = Window::new(65536, 16384);
+        assert_eq!(
+            mkimage(false).export_tiff_float(huge, Vec::new()),
+            Err(EncoderError::ImageTooLarge)
+        );
+    }
+
+    #[test]
+    fn export_hdr_tiff() {
+        let ldr = mkimage(false);
+        let hdr = mkimage(true);
+
+        assert!(ldr.hdr_pixels().is_none());
+        assert_eq!(ldr.pixels(), hdr.pixels());
+
+        let wnd = Window::new(16, 8).at(12, 6);
+
+        let ldr_img = ldr
+            .export_window_image(wnd, ImageFormat::TiffLinearFloat)
+            .unwrap();
+        let hdr_img = hdr
+            .export_window_image(wnd, ImageFormat::TiffLinearFloat)
+            .unwrap();
+
+        let ldr_peak = samples(&ldr_img).into_iter().fold(0.0, f32::max);
+        let hdr_peak = samples(&hdr_img).into_iter().fold(0.0, f32::max);
+
+        // The LDR image is clipped at the full scale.
+        assert!((ldr_peak - 1.0).abs() < f32::EPSILON);
+        assert!(hdr_peak > 3.9, "hdr_peak = {hdr_peak}");
+
+        // The background samples match.
+        let bg = f32::from(1000u16) / 65535.0;
+        assert!((samples(&hdr_img)[0] - bg).abs() < 1e-6);
+        assert!((samples(&ldr_img)[0] - bg).abs() < 1e-6);
+
+        let (w, _) = hdr.dimensions();
+        let peak = hdr.hdr_pixels().unwrap()[10 * w as usize + 20];
+        assert!((peak - hdr_peak).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn hdr_rendering_state() {
+        let mut c = mkimage(true);
+        assert_eq!(c.hdr_pixels().unwrap().len(), 64 * 32);
+
+        assert_eq!(
+            c.export_subsampled_image((2, 2), ImageFormat::TiffLinearFloat),
+            Err(EncoderError::NotImplemented)
+        );
+
+        c.set_color_mode(crate::ColorMode::Rgb);
+        assert_eq!(c.hdr_pixels().unwrap().len(), 3 * 64 * 32);
+
+        c.set_hdr_rendering(false);
+        assert!(c.hdr_pixels().is_none());
+    }
+}
@@ -0,0 +1,272 @@
+//! Planetarium
+//! ===========
+//!
+//! YUV4MPEG2 video stream export routines
+//! --------------------------------------
+//!
+//! Defines the public Y4M chroma format enum `Y4mChroma`
+//! and the YUV4MPEG2 video stream writer `Y4mWriter`.
+
+use std::io::Write;
+
+use super::SampleWriter;
+use crate::{Canvas, CanvasPixel, EncoderError, ImageFormat, Window};
+
+/// Neutral chroma sample value
+const NEUTRAL_CHROMA: u8 = 128;
+
+/// Y4M video frame chroma formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Y4mChroma {
+    /// Luma plane only (`Cmono`)
+    Mono,
+    /// Luma plane and 2x2 subsampled neutral chroma planes (`C420jpeg`)
+    Yuv420,
+}
+
+impl Y4mChroma {
+    /// Returns the Y4M stream header colorspace parameter value.
+    fn colorspace(self) -> &'static str {
+        match self {
+            Y4mChroma::Mono => "mono",
+            Y4mChroma::Yuv420 => "420jpeg",
+        }
+    }
+}
+
+/// YUV4MPEG2 (Y4M) raw video stream writer
+///
+/// Encodes the successive canvas images as Y4M video frames.
+/// The luma plane contains the 8-bit gamma-compressed samples
+/// as in the `RawGamma8Bpp` image format. The optional chroma planes
+/// are filled with the neutral (gray) chroma value.
+///
+/// The stream header is written by `Y4mWriter::new()`, the frame dimensions
+/// are taken from the canvas passed to it.
+///
+/// Usage
+/// -----
+///
+/// ```
+/// use planetarium::{Canvas, SpotShape, Y4mChroma, Y4mWriter};
+///
+/// let mut c = Canvas::new(64, 64);
+/// let spot = c.add_spot((10.0, 20.0), SpotShape::default().scale(2.5), 0.8);
+///
+/// let mut buf = Vec::new();
+///
+/// // Encode 25 FPS video frames with neutral chroma planes.
+/// let mut y4m = Y4mWriter::new(&c, &mut buf, (25, 1), Y4mChroma::Yuv420).unwrap();
+///
+/// for i in 0..10 {
+///     // Move the spot across the canvas.
+///     c.set_spot_offset(spot, (4.0 * i as f32, 2.0 * i as f32));
+///     c.draw();
+///
+///     y4m.write_frame(&c).unwrap();
+/// }
+///
+/// y4m.finish().unwrap();
+/// ```
+pub struct Y4mWriter<W: Write> {
+    /// Underlying video stream writer
+    writer: W,
+
+    /// Frame chroma format
+    chroma: Y4mChroma,
+
+    /// Frame dimensions in pixels
+    dimensions: (u32, u32),
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Creates a new Y4M video stream writer and writes the stream header
+    /// into `writer`.
+    ///
+    /// The frame rate is defined as a `(numerator, denominator)` fraction
+    /// in frames per second.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the canvas color mode
+    /// is not compatible with the `RawGamma8Bpp` image format.
+    ///
+    /// Returns [`EncoderError::InvalidParameters`] if the frame rate is invalid.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn new<P: CanvasPixel>(
+        canvas: &Canvas<P>,
+        mut writer: W,
+        framerate: (u32, u32),
+        chroma: Y4mChroma,
+    ) -> Result<Self, EncoderError> {
+        canvas.validate_color_mode(ImageFormat::RawGamma8Bpp)?;
+
+        if framerate.0 == 0 || framerate.1 == 0 {
+            return Err(EncoderError::InvalidParameters);
+        }
+
+        let (width, height) = canvas.dimensions();
+
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C{} XCOLORRANGE=FULL",
+            width,
+            height,
+            framerate.0,
+            framerate.1,
+            chroma.colorspace()
+        )?;
+
+        Ok(Y4mWriter {
+            writer,
+            chroma,
+            dimensions: (width, height),
+        })
+    }
+
+    /// Encodes the canvas image as the next video frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::BrokenWindow`] if the canvas dimensions
+    /// do not match the video frame dimensions.
+    ///
+    /// Returns [`EncoderError::IncompatibleColorMode`] if the canvas color mode
+    /// is not compatible with the `RawGamma8Bpp` image format.
+    ///
+    /// Returns [`EncoderError::IoError`] if writing the frame fails.
+    pub fn write_frame<P: CanvasPixel>(&mut self, canvas: &Canvas<P>) -> Result<(), EncoderError> {
+        if canvas.dimensions() != self.dimensions {
+            return Err(EncoderError::BrokenWindow);
+        }
+
+        canvas.validate_color_mode(ImageFormat::RawGamma8Bpp)?;
+
+        let (width, height) = self.dimensions;
+        let window = Window::new(width, height);
+
+        self.writer.write_all(b"FRAME\n")?;
+        canvas.export_raw8bpp(window, &mut self.writer)?;
+
+        if self.chroma == Y4mChroma::Yuv420 {
+            // Cb and Cr planes subsampled by 2 with rounding up
+            let len = 2 * width.div_ceil(2) as usize * height.div_ceil(2) as usize;
+            let mut rawbuf = SampleWriter::new(&mut self.writer);
+
+            for _ in 0..len {
+                rawbuf.write(&[NEUTRAL_CHROMA])?;
+            }
+
+            rawbuf.finish()?;
+        }
+
+        Ok(())
+    }
+
+    /// Finishes the Y4M video stream by flushing the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::IoError`] if flushing the underlying writer fails.
+    pub fn finish(mut self) -> Result<(), EncoderError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorMode, SpotShape};
+
+    /// Creates a 33x17 canvas image with a light spot.
+    fn mkimage() -> Canvas {
+        let mut c = Canvas::new(33, 17);
+        c.set_background(1000);
+        c.add_spot((10.5, 8.2), SpotShape::default().scale(2.5), 0.8);
+
+        c.draw();
+        c
+    }
+
+    #[test]
+    fn export_y4m_mono() {
+        let c = mkimage();
+
+        let mut buf = Vec::new();
+        let mut y4m = Y4mWriter::new(&c, &mut buf, (30000, 1001), Y4mChroma::Mono).unwrap();
+        y4m.write_frame(&c).unwrap();
+        y4m.write_frame(&c).unwrap();
+        y4m.finish().unwrap();
+
+        let header = b"YUV4MPEG2 W33 H17 F30000:1001 Ip A1:1 Cmono XCOLORRANGE=FULL\n";
+        assert_eq!(buf[..header.len()], header[..]);
+
+        let img = c.export_image(ImageFormat::RawGamma8Bpp).unwrap();
+        let frame = [&b"FRAME\n"[..], &img].concat();
+
+        let frames = &buf[header.len()..];
+        assert_eq!(frames, [&frame[..], &frame].concat());
+    }
+
+    #[test]
+    fn export_y4m_yuv420() {
+        let c = mkimage();
+
+        let mut buf = Vec::new();
+        let mut y4m = Y4mWriter::new(&c, &mut buf, (25, 1), Y4mChroma::Yuv420).unwrap();
+        y4m.write_frame(&c).unwrap();
+        y4m.finish().unwrap();
+
+        let header = b"YUV4MPEG2 W33 H17 F25:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL\n";
+        assert_eq!(buf[..header.len()], header[..]);
+
+        let frame = &buf[header.len()..];
+        let (luma, chroma) = frame[6..].split_at(33 * 17);
+        assert_eq!(&frame[..6], b"FRAME\n");
+        assert_eq!(luma, c.export_image(ImageFormat::RawGamma8Bpp).unwrap());
+        assert_eq!(chroma.len(), 2 * 17 * 9);
+        assert!(chroma.iter().all(|&v| v == NEUTRAL_CHROMA));
+    }
+
+    #[test]
+    fn y4m_errors() {
+        let mut c = Canvas::new(16, 16);
+        let mut buf = Vec::new();
+
+        assert!(matches!(
+            Y4mWriter::new(&c, &mut buf, (0, 1), Y4mChroma::Mono),
+            Err(EncoderError::InvalidParameters)
+        ));
+
+        c.set_color_mode(ColorMode::Rgb);
+        assert!(matches!(
+            Y4mWriter::new(&c, &mut buf, (25, 1), Y4mChroma::Mono),
+            Err(EncoderError::IncompatibleColorMode)
+        ));
+
+        c.set_color_mode(ColorMode::Monochrome);
+        let mut y4m = Y4mWriter::new(&c, &mut buf, (25, 1), Y4mChroma::Mono).unwrap();
+
+        assert_eq!(
+            y4m.write_frame(&Canvas::new(16, 8)),
+            Err(EncoderError::BrokenWindow)
+        );
+
+        c.set_color_mode(ColorMode::Rgb);
+        assert_eq!(
+            y4m.write_frame(&c),
+            Err(EncoderError::IncompatibleColorMode)
+        );
+
+        // The output slice is too short.
+        let mut bytes = [0u8; 100];
+        c.set_color_mode(ColorMode::Monochrome);
+        let mut y4m = Y4mWriter::new(&c, &mut bytes[..], (25, 1), Y4mChroma::Mono).unwrap();
+        assert_eq!(
+            y4m.write_frame(&c),
+            Err(EncoderError::IoError(std::io::ErrorKind::WriteZero))
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Planetarium
+//! ===========
+//!
+//! Private light spot spatial index definitions
+//! --------------------------------------------
+//!
+//! Defines a new opaque private structure `SpotIndex`
+//! implementing a uniform grid index of the light spot
+//! world coordinates.
+
+use std::collections::HashMap;
+
+use crate::{Point, SpotId, SpotShape, Transform};
+
+/// Grid cell coordinates
+type Cell = (i32, i32);
+
+/// Opaque light spot world coordinates grid index object
+#[derive(Debug, Clone)]
+pub(crate) struct SpotIndex {
+    /// Grid cell size in world coordinate units
+    cell_size: f32,
+
+    /// Light spot lists indexed by the grid cell coordinates
+    cells: HashMap<Cell, Vec<SpotId>>,
+
+    /// Grid cell coordinates indexed by the light spot ID
+    spot_cells: Vec<Cell>,
+
+    /// Maximum effective spot radius in canvas pixels as XY components
+    radius: (f32, f32),
+}
+
+#[allow(clippy::cast_possible_truncation)]
+impl SpotIndex {
+    /// Creates an empty light spot index with the grid cell size
+    /// in world coordinate units.
+    ///
+    /// The cell size is validated by the caller.
+    #[must_use]
+    pub(crate) fn new(cell_size: f32) -> Self {
+        debug_assert!(cell_size.is_finite() && cell_size > 0.0);
+
+        SpotIndex {
+            cell_size,
+            cells: HashMap::new(),
+            spot_cells: Vec::new(),
+            radius: (0.0, 0.0),
+        }
+    }
+
+    /// Returns the grid cell size in world coordinate units.
+    #[must_use]
+    pub(crate) fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Returns the maximum effective spot radius in canvas pixels.
+    #[must_use]
+    pub(crate) fn max_radius(&self) -> (f32, f32) {
+        self.radius
+    }
+
+    /// Calculates the grid cell coordinates for the world position.
+    #[must_use]
+    fn cell(&self, position: Point) -> Cell {
+        // Float to integer casts saturate at the integer type range.
+        (
+            (position.0 / self.cell_size).floor() as i32,
+            (position.1 / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Inserts the next light spot with the world position into the index.
+    pub(crate) fn insert(&mut self, spot: SpotId, position: Point, shape: &SpotShape) {
+        debug_assert_eq!(spot, self.spot_cells.len());
+
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(spot);
+        self.spot_cells.push(cell);
+
+        let (rx, ry) = shape.effective_radius_xy();
+        self.radius = (self.radius.0.max(rx), self.radius.1.max(ry));
+    }
+
+    /// Moves the indexed light spot to the new world position.
+    pub(crate) fn update(&mut self, spot: SpotId, position: Point) {
+        let cell = self.cell(position);
+        let old_cell = self.spot_cells[spot];
+
+        if cell == old_cell {
+            return;
+        }
+
+        if let Some(spots) = self.cells.get_mut(&old_cell) {
+            spots.retain(|&s| s != spot);
+
+            if spots.is_empty() {
+                self.cells.remove(&old_cell);
+            }
+        }
+
+        self.cells.entry(cell).or_default().push(spot);
+        self.spot_cells[spot] = cell;
+    }
+
+    /// Returns the light spots in the grid cells intersecting
+    /// the `min`..`max` world rectangle sorted by the spot ID.
+    ///
+    /// The rectangle is extended by one grid cell to account
+    /// for the rounding errors.
+    #[must_use]
+    pub(crate) fn query(&self, min: Point, max: Point) -> Vec<SpotId> {
+        let (x0, y0) = self.cell(min);
+        let (x1, y1) = self.cell(max);
+
+        let (x0, y0) = (x0.saturating_sub(1), y0.saturating_sub(1));
+        let (x1, y1) = (x1.saturating_add(1), y1.saturating_add(1));
+
+        let mut spots = Vec::new();
+
+        // Visit the occupied cells directly when the rectangle is sparse.
+        let area =
+            (i64::from(x1) - i64::from(x0) + 1).saturating_mul(i64::from(y1) - i64::from(y0) + 1);
+
+        if area > i64::try_from(self.cells.len()).unwrap_or(i64::MAX) {
+            for (&(x, y), cell) in &self.cells {
+                if (x0..=x1).contains(&x) && (y0..=y1).contains(&y) {
+                    spots.extend_from_slice(cell);
+                }
+            }
+        } else {
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    if let Some(cell) = self.cells.get(&(x, y)) {
+                        spots.extend_from_slice(cell);
+                    }
+                }
+            }
+        }
+
+        spots.sort_unstable();
+        spots
+    }
+}
+
+impl Transform {
+    /// Maps the `min`..`max` canvas rectangle to the bounding rectangle
+    /// in world coordinates.
+    ///
+    /// Returns `None` if the transformation matrix is singular.
+    #[must_use]
+    pub(crate) fn inverse_rect(&self, min: Point, max: Point) -> Option<(Point, Point)> {
+        let det = self.xx * self.yy - self.xy * self.yx;
+
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let inv_det = det.recip();
+
+        let inverse = |(x, y): Point| {
+            let (dx, dy) = (x - self.tx, y - self.ty);
+
+            (
+                inv_det * (self.yy * dx - self.xy * dy),
+                inv_det * (self.xx * dy - self.yx * dx),
+            )
+        };
+
+        let corners = [min, (max.0, min.1), (min.0, max.1), max].map(inverse);
+
+        let fold = |f: fn(f32, f32) -> f32, init: Point| {
+            corners
+                .iter()
+                .fold(init, |acc, p| (f(acc.0, p.0), f(acc.1, p.1)))
+        };
+
+        let wmin = fold(f32::min, corners[0]);
+        let wmax = fold(f32::max, corners[0]);
+
+        Some((wmin, wmax))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_cells() {
+        let shape = SpotShape::default();
+        let mut index = SpotIndex::new(10.0);
+
+        index.insert(0, (5.0, 5.0), &shape);
+        index.insert(1, (-15.0, 25.0), &shape);
+        index.insert(2, (105.0, 5.0), &shape);
+        index.insert(3, (8.0, 2.0), &shape.scale(3.0));
+
+        assert_eq!(index.max_radius(), shape.scale(3.0).effective_radius_xy());
+
+        assert_eq!(index.query((0.0, 0.0), (9.0, 9.0)), [0, 3]);
+        assert_eq!(index.query((-20.0, 0.0), (9.0, 30.0)), [0, 1, 3]);
+        assert_eq!(index.query((200.0, 200.0), (300.0, 300.0)), []);
+
+        // Huge rectangles visit the occupied cells only.
+        assert_eq!(index.query((-1e30, -1e30), (1e30, 1e30)), [0, 1, 2, 3]);
+
+        index.update(0, (95.0, 0.0));
+        index.update(3, (8.5, 2.5));
+
+        assert_eq!(index.query((0.0, 0.0), (9.0, 9.0)), [3]);
+        assert_eq!(index.query((100.0, 0.0), (110.0, 9.0)), [0, 2]);
+    }
+
+    #[test]
+    fn inverse_rects() {
+        let transform = Transform::default().translate((10.0, -5.0)).scale(2.0);
+
+        let (min, max) = transform.inverse_rect((0.0, 0.0), (20.0, 10.0)).unwrap();
+        assert_eq!(min, (-10.0, 5.0));
+        assert_eq!(max, (0.0, 10.0));
+
+        let rotated = Transform::default().rotate(90.0);
+        let (min, max) = rotated.inverse_rect((0.0, 0.0), (10.0, 20.0)).unwrap();
+        assert!((min.0 - 0.0).abs() < 1e-4 && (min.1 + 10.0).abs() < 1e-4);
+        assert!((max.0 - 20.0).abs() < 1e-4 && max.1.abs() < 1e-4);
+
+        let singular = Transform::from(0.0);
+        assert_eq!(singular.inverse_rect((0.0, 0.0), (1.0, 1.0)), None);
+    }
+}
@@ -99,13 +99,275 @@
 //! assert_eq!(c.spot_position(spot2), Some((80.6 + 114.2 + 13.7, 200.2 - 73.3 - 20.3)));
 //! ```
 //!
+//! Color image rendering
+//! ---------------------
+//!
+//! The `Canvas` object can emulate raw color image sensors
+//! with a Bayer color filter array (CFA) mosaic.
+//!
+//! Each light spot carries the red, green and blue color channel weights,
+//! which are applied to the canvas pixels according to the CFA pattern.
+//!
+//! ```
+//! use planetarium::{BayerPattern, Canvas, ColorMode, SpotColor, SpotShape, Window};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! // Render a raw color image with the RGGB Bayer filter mosaic.
+//! c.set_color_mode(ColorMode::Bayer(BayerPattern::Rggb));
+//!
+//! let spot = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.8);
+//!
+//! // Set the spot color channel weights.
+//! c.set_spot_color(spot, SpotColor::new(1.0, 0.7, 0.4));
+//!
+//! c.draw();
+//!
+//! // The mosaic pattern of a window image depends on the window origin.
+//! let wnd = Window::new(32, 16).at(101, 150);
+//! assert_eq!(c.exported_bayer_pattern(wnd, (1, 1)), Some(BayerPattern::Grbg));
+//! ```
+//!
+//! Full color images with interleaved R, G, B channel samples can be rendered
+//! in the RGB color mode and exported to the RGB color PNG image formats.
+//!
+//! ```
+//! use planetarium::{Canvas, ColorMode, ImageFormat, SpotColor, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.set_color_mode(ColorMode::Rgb);
+//!
+//! let spot = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.8);
+//! // Approximate the star color from the B-V color index.
+//! c.set_spot_color(spot, SpotColor::from_bv(1.1));
+//!
+//! c.draw();
+//!
+//! #[cfg(feature = "png")]
+//! // Export to a 8-bit gamma-compressed RGB PNG image.
+//! let png_rgb8_bytes = c.export_image(ImageFormat::PngRgbGamma8Bpp).unwrap();
+//! ```
+//!
+//! Floating point accumulation
+//! ---------------------------
+//!
+//! By default each light spot contribution is truncated to an integer
+//! pixel value and added to the pixel buffer with saturation.
+//! The optional floating point accumulation buffer sums all light spots
+//! before quantizing the pixel values with rounding at the end of `draw()`.
+//! The quantization full scale level defines the pixel value of the unit
+//! spot intensity above the background level.
+//!
+//! ```
+//! use planetarium::{Canvas, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! // Quantize the accumulated samples to the 12-bit pixel range.
+//! c.set_float_accumulation(Some(4095.0));
+//!
+//! // Many faint overlapping spots
+//! for _ in 0..100 {
+//!     c.add_spot((100.0, 130.0), SpotShape::default(), 0.002);
+//! }
+//!
+//! c.draw();
+//!
+//! // The pixel values are quantized after summing up the spots.
+//! assert!(c.pixels()[130 * 256 + 100].abs_diff(819) <= 1);
+//! ```
+//!
+//! Canvas pixel types
+//! ------------------
+//!
+//! The `Canvas` object is generic over the pixel sample type implementing
+//! the `CanvasPixel` trait: `u8`, `u16` (default), `u32` and `f32`.
+//! The unit light spot intensity corresponds to the pixel type full scale:
+//! the maximum value for the integer pixel types and 1.0 for `f32`.
+//! The floating point pixels are not saturated.
+//!
+//! The image export encoders convert the pixel samples
+//! to the 16-bit linear light samples.
+//!
+//! ```
+//! use planetarium::{Canvas, SpotShape};
+//!
+//! // Draw on a floating point 256x256 pixel canvas.
+//! let mut c = Canvas::<f32>::with_dimensions(256, 256);
+//!
+//! c.add_spot((100.0, 130.0), SpotShape::default().scale(2.5), 1.5);
+//! c.draw();
+//!
+//! // Overexposed pixel value
+//! assert_eq!(c.pixels()[130 * 256 + 100], 1.5);
+//! ```
+//!
+//! External frame buffers
+//! ----------------------
+//!
+//! The light spots can be drawn directly into a caller-owned frame buffer,
+//! e.g. a shared memory or a DMA buffer, with a custom row stride.
+//!
+//! ```
+//! use planetarium::{Canvas, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.set_background(100);
+//! c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.8);
+//!
+//! // Frame buffer rows padded to 320 pixels
+//! let mut framebuf = vec![0u16; 320 * 256];
+//!
+//! c.draw_into(&mut framebuf, 256, 256, 320).unwrap();
+//! assert_eq!(framebuf[0], 100);
+//! ```
+//!
+//! Pixel buffer row stride
+//! -----------------------
+//!
+//! The canvas pixel buffer rows can be padded to a custom row stride,
+//! e.g. to align each row to a 64-byte boundary. The padding samples are
+//! not exported, so the exported images are always tightly packed.
+//!
+//! ```
+//! use planetarium::Canvas;
+//!
+//! let mut c = Canvas::new(100, 100);
+//!
+//! // Align the 16-bit pixel rows to 64 bytes.
+//! c.set_row_stride(128).unwrap();
+//! c.draw();
+//!
+//! let stride = c.row_stride();
+//! assert_eq!(c.pixels().len(), 100 * stride);
+//!
+//! // Get pixel at x = 10, y = 20.
+//! let val_x_y = c.pixels()[20 * stride + 10];
+//! ```
+//!
+//! Multi-threaded rendering
+//! ------------------------
+//!
+//! The optional `rayon` feature enables the multi-threaded light spot
+//! rendering in `Canvas::draw()`. The canvas image is split into horizontal
+//! bands rendered in parallel, the rendered images are identical
+//! to the single-threaded ones.
+//!
+//! Incremental redraw
+//! ------------------
+//!
+//! The canvas tracks the image regions changed by the light spot additions
+//! and the spot offset, illumination and color changes. `Canvas::redraw()`
+//! redraws only these dirty regions, which is much faster than the full
+//! `Canvas::draw()` when only a few light spots change between frames.
+//! The redrawn image is identical to the fully drawn one.
+//!
+//! ```
+//! use planetarium::{Canvas, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! let spot1 = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.8);
+//! let spot2 = c.add_spot((50.0, 20.0), SpotShape::default(), 0.5);
+//!
+//! // The first redraw draws the whole canvas.
+//! c.redraw();
+//!
+//! // Only the old and the new `spot1` regions and the `spot2` region
+//! // are redrawn.
+//! c.set_spot_offset(spot1, (1.5, -0.5));
+//! c.set_spot_illumination(spot2, 1.2);
+//! c.redraw();
+//! ```
+//!
+//! Window region drawing
+//! ---------------------
+//!
+//! When only a canvas window image is exported, the light spots can be drawn
+//! onto the window region only with `Canvas::draw_window()`. The drawing cost
+//! scales with the window size rather than with the canvas size.
+//!
+//! ```
+//! use planetarium::{Canvas, ImageFormat, SpotShape, Window};
+//!
+//! let mut c = Canvas::new(4096, 4096);
+//!
+//! c.add_spot((1000.3, 1030.8), SpotShape::default().scale(2.5), 0.8);
+//!
+//! let wnd = Window::new(64, 64).at(980, 1000);
+//!
+//! // Draw and export the window region only.
+//! c.draw_window(wnd).unwrap();
+//! let image = c.export_window_image(wnd, ImageFormat::RawGamma8Bpp).unwrap();
+//! ```
+//!
+//! Spatial index
+//! -------------
+//!
+//! Large light spot catalogs with most of the spots outside of the view
+//! can be indexed by a uniform grid over the world coordinates.
+//! The off-canvas spots are culled using the index when drawing.
+//! The light spots intersecting a canvas window rectangle can be queried
+//! with `Canvas::spots_in_window()` with or without the index.
+//!
+//! ```
+//! use planetarium::{Canvas, SpotShape, Transform, Window};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! // Index the spot world positions with 64x64 grid cells.
+//! c.set_spatial_index(Some(64.0)).unwrap();
+//!
+//! for i in 0..1000 {
+//!     let position = ((i % 40) as f32 * 100.0, (i / 40) as f32 * 100.0);
+//!     c.add_spot(position, SpotShape::default(), 0.5);
+//! }
+//!
+//! c.set_view_transform(Transform::default().translate((-1000.0, -1000.0)));
+//! c.draw();
+//!
+//! let spots = c.spots_in_window(Window::new(256, 256));
+//! assert_eq!(spots.len(), 9);
+//! ```
+//!
+//! Light spot pattern LUT
+//! ----------------------
+//!
+//! The light spot intensity pattern is evaluated using a pre-computed
+//! Airy disc function LUT. The default 1024-sample LUT is looked up without
+//! interpolation, which can cause stair-stepping in the profiles of large
+//! light spots. The LUT size and the interpolation mode are configurable.
+//!
+//! ```
+//! use planetarium::{Canvas, PatternInterpolation, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.add_spot((128.0, 128.0), SpotShape::default().scale(25.0), 0.8);
+//!
+//! // Use a larger LUT with linear interpolation between the samples.
+//! c.set_pattern_lut(4096, PatternInterpolation::Linear).unwrap();
+//! c.draw();
+//! ```
+//!
 //! Canvas image export
 //! -------------------
 //!
-//! The `Canvas` object supports image export to RAW and PNG file formats.
-//! Both 8-bit and 16-bit PNG sample formats are supported.
+//! The `Canvas` object supports image export to RAW, NumPy array, TIFF
+//! and PNG file formats and SER and Y4M video export.
+//! Both 8-bit and 16-bit PNG sample formats are supported
+//! in grayscale and RGB color variants.
 //! Export to PNG formats requires the default `png` feature to be enabled.
 //!
+//! The NumPy `.npy` array files contain the linear light samples
+//! with the `(height, width)` array shape for the full, window
+//! and subsampled image exports.
+//!
+//! The PNG compression level and filter type can be configured
+//! with `Canvas::set_export_options()`.
+//!
 //! ### Example RAW image export code
 //!
 //! ```
@@ -124,6 +386,15 @@
 //!
 //! // Export to a 12-bit gamma-compressed grayscale little-endian RAW image.
 //! let raw_12bpp_bytes = c.export_image(ImageFormat::RawLinear12BppLE).unwrap();
+//!
+//! // Export to a 16-bit linear light grayscale little-endian RAW image.
+//! let raw_16bpp_bytes = c.export_image(ImageFormat::RawLinear16BppLE).unwrap();
+//!
+//! // Export to a 16-bit linear light grayscale NumPy `.npy` array file.
+//! let npy_16bpp_bytes = c.export_image(ImageFormat::NpyLinear16Bpp).unwrap();
+//!
+//! // Export to a normalized `float32` NumPy `.npy` array file.
+//! let npy_float_bytes = c.export_image(ImageFormat::NpyLinearFloat).unwrap();
 //! ```
 //!
 //! ### Example PNG export code
@@ -145,6 +416,171 @@
 //! let png_16bpp_bytes = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
 //! ```
 //!
+//! ### Example HDR image export code
+//!
+//! The optional floating point HDR rendering buffer preserves the light
+//! spot intensities above the 16-bit pixel full scale. The HDR samples
+//! are exported with the `TiffLinearFloat` image format.
+//!
+//! ```
+//! use planetarium::{Canvas, ImageFormat, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! // Enable the floating point HDR rendering.
+//! c.set_hdr_rendering(true);
+//!
+//! // Overexposed light spot
+//! c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 5.0);
+//! c.draw();
+//!
+//! // The HDR samples are not clipped at the full scale.
+//! let peak = c.hdr_pixels().unwrap().iter().copied().fold(0.0, f32::max);
+//! assert!(peak > 1.0);
+//!
+//! // Export to a 32-bit floating point linear light grayscale TIFF image.
+//! let tiff_bytes = c.export_image(ImageFormat::TiffLinearFloat).unwrap();
+//! ```
+//!
+//! ### Example animated PNG export code
+//!
+//! The `ApngWriter` object encodes a sequence of canvas images
+//! as animated PNG frames with a fixed frame delay.
+//!
+//! ```
+//! use planetarium::{Canvas, ImageFormat, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! let spot = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.5);
+//!
+//! #[cfg(feature = "png")]
+//! {
+//!     let mut apng_bytes = Vec::new();
+//!     let fmt = ImageFormat::PngGamma8Bpp;
+//!
+//!     // Write 25 frames displayed for 40 ms each.
+//!     let mut apng = planetarium::ApngWriter::new(&c, &mut apng_bytes, 25, 40, fmt).unwrap();
+//!
+//!     for i in 0..25 {
+//!         // Move the spot between the frames.
+//!         c.set_spot_offset(spot, (0.5 * i as f32, 0.0));
+//!         c.draw();
+//!
+//!         apng.write_frame(&c).unwrap();
+//!     }
+//!
+//!     apng.finish().unwrap();
+//! }
+//! ```
+//!
+//! ### Example SER video export code
+//!
+//! The `SerWriter` object encodes a sequence of canvas images
+//! as SER astronomy video frames with per-frame timestamps.
+//!
+//! ```
+//! use std::time::{Duration, SystemTime};
+//!
+//! use planetarium::{Canvas, ImageFormat, SerWriter, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! let spot = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.5);
+//!
+//! let mut ser_bytes = Vec::new();
+//! let fmt = ImageFormat::RawLinear16BppLE;
+//!
+//! // Write 25 frames with 16-bit samples.
+//! let mut ser = SerWriter::new(&c, &mut ser_bytes, 25, fmt).unwrap();
+//! let start = SystemTime::now();
+//!
+//! for i in 0..25 {
+//!     // Move the spot between the frames.
+//!     c.set_spot_offset(spot, (0.5 * i as f32, 0.0));
+//!     c.draw();
+//!
+//!     ser.write_frame(&c, start + Duration::from_millis(40 * i)).unwrap();
+//! }
+//!
+//! ser.finish().unwrap();
+//! ```
+//!
+//! ### Example Y4M video export code
+//!
+//! The `Y4mWriter` object encodes a sequence of canvas images
+//! as a YUV4MPEG2 raw video stream suitable for the video encoding tools.
+//!
+//! ```
+//! use planetarium::{Canvas, SpotShape, Y4mChroma, Y4mWriter};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! let spot = c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.5);
+//!
+//! let mut y4m_bytes = Vec::new();
+//!
+//! // Write 25 FPS frames with neutral 4:2:0 chroma planes.
+//! let mut y4m = Y4mWriter::new(&c, &mut y4m_bytes, (25, 1), Y4mChroma::Yuv420).unwrap();
+//!
+//! for i in 0..25 {
+//!     // Move the spot between the frames.
+//!     c.set_spot_offset(spot, (0.5 * i as f32, 0.0));
+//!     c.draw();
+//!
+//!     y4m.write_frame(&c).unwrap();
+//! }
+//!
+//! y4m.finish().unwrap();
+//! ```
+//!
+//! ### Example streaming image export code
+//!
+//! The `export_*_to()` methods encode the image data directly
+//! into a caller-provided `std::io::Write` implementation.
+//!
+//! ```
+//! use planetarium::{Canvas, ImageFormat};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.set_background(1000);
+//! c.clear();
+//!
+//! // Any `std::io::Write` implementation can be used here,
+//! // e.g. `std::fs::File` or `std::net::TcpStream`.
+//! let mut out: Vec<u8> = Vec::new();
+//!
+//! // Export to a 10-bit linear light grayscale little-endian RAW image.
+//! c.export_image_to(ImageFormat::RawLinear10BppLE, &mut out).unwrap();
+//!
+//! assert_eq!(out.len(), 2 * 256 * 256);
+//! ```
+//!
+//! ### Example zero-allocation RAW image export code
+//!
+//! The `export_*_into()` methods encode the RAW image data into
+//! a caller-provided byte buffer, which can be reused for many frames.
+//!
+//! ```
+//! use planetarium::{Canvas, ImageFormat};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! // Preallocated output buffer
+//! let mut buf = vec![0u8; 2 * 256 * 256];
+//!
+//! for frame in 0..4 {
+//!     c.set_background(1000 + frame);
+//!     c.draw();
+//!
+//!     // Export to a 12-bit linear light grayscale little-endian RAW image.
+//!     let len = c.export_image_into(ImageFormat::RawLinear12BppLE, &mut buf).unwrap();
+//!
+//!     assert_eq!(len, 2 * 256 * 256);
+//! }
+//! ```
+//!
 //! Window image export
 //! -------------------
 //!
@@ -176,18 +612,46 @@
 //! assert_eq!(raw_window_bytes.len(), 32 * 16);
 //! ```
 //!
+//! ### Example multiple window image export code
+//!
+//! Multiple non-overlapping windows can be exported in a single call
+//! either as separate images or as a concatenated image data buffer
+//! with an offset table.
+//!
+//! ```
+//! use planetarium::{Canvas, ImageFormat, Window};
+//!
+//! let c = Canvas::new(256, 256);
+//!
+//! let windows = [
+//!     Window::new(16, 16).at(20, 30),
+//!     Window::new(16, 16).at(120, 200),
+//! ];
+//!
+//! let fmt = ImageFormat::RawGamma8Bpp;
+//!
+//! // Export to the concatenated window image bytes.
+//! let (raw_bytes, offsets) = c.export_concatenated_window_images(&windows, fmt).unwrap();
+//!
+//! // The window images are stored back to back.
+//! assert_eq!(offsets, [0, 16 * 16]);
+//! assert_eq!(raw_bytes.len(), 2 * 16 * 16);
+//! ```
+//!
 //! Subsampled image export
 //! -----------------------
 //!
 //! The `Canvas` object additionally supports subsampled image export
 //! with independent row and column subsampling factors.
 //!
-//! Only whole canvas images can be exported with subsampling.
+//! Canvas window images can be exported with subsampling, too.
+//!
+//! The window dimensions must be divisible by the subsampling factors.
 //!
 //! ### Example subsampled image export code
 //!
 //! ```
-//! use planetarium::{Canvas, ImageFormat};
+//! use planetarium::{Canvas, ImageFormat, Window};
 //!
 //! let mut c = Canvas::new(256, 256);
 //!
@@ -204,19 +668,121 @@
 //!
 //! // Count 4x2 subsampled image pixels.
 //! assert_eq!(raw_sub_bytes.len(), 2 * 64 * 128);
+//!
+//! // Export the subsampled 64x32 window image.
+//! let wnd = Window::new(64, 32).at(100, 50);
+//! let raw_wnd_bytes = c.export_subsampled_window_image(wnd, factors, fmt).unwrap();
+//!
+//! // Count 4x2 subsampled window image pixels.
+//! assert_eq!(raw_wnd_bytes.len(), 2 * 16 * 16);
+//! ```
+//!
+//! ### Example binned image export code
+//!
+//! The subsampled images can be binned instead of decimated:
+//! the pixel values in each subsampling block are summed or averaged.
+//! The Bayer color mode canvas images are binned by color channel.
+//!
+//! ```
+//! use planetarium::{Binning, Canvas, ImageFormat};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.set_background(1000);
+//! c.clear();
+//!
+//! let fmt = ImageFormat::RawLinear12BppLE;
+//!
+//! // Export to the 2x2 binned canvas image bytes.
+//! let raw_bin_bytes = c.export_binned_image((2, 2), Binning::Sum, fmt).unwrap();
+//!
+//! // The binned pixel values are summed.
+//! assert_eq!(raw_bin_bytes[..2], (4000u16 >> 4).to_le_bytes());
+//! ```
+//!
+//! Ground truth data export
+//! ------------------------
+//!
+//! The rendered light spot parameters (canvas coordinates, effective
+//! peak intensity, shape matrix, effective radius and visibility)
+//! can be exported as JSON or CSV text for scoring the image
+//! processing algorithms against the ground truth.
+//!
+//! ### Example ground truth export code
+//!
+//! ```
+//! use planetarium::{Canvas, GroundTruthFormat, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.5);
+//! c.add_spot((80.6, 200.2), SpotShape::default(), 0.9);
+//!
+//! // Export the spot list as a CSV table.
+//! let csv = c.export_ground_truth(GroundTruthFormat::Csv);
+//!
+//! // The header line is followed by the spot lines.
+//! assert_eq!(csv.lines().count(), 3);
+//! ```
+//!
+//! ### Example PNG metadata export code
+//!
+//! The canvas rendering parameters and the light spot list can be
+//! embedded into the exported PNG images as text chunks and parsed back
+//! to make the test images self-describing.
+//!
+//! ```
+//! use planetarium::{Canvas, ExportOptions, ImageFormat, ImageMetadata, SpotShape};
+//!
+//! let mut c = Canvas::new(256, 256);
+//!
+//! c.add_spot((100.3, 130.8), SpotShape::default().scale(2.5), 0.5);
+//!
+//! let mut options = ExportOptions::default();
+//! options.metadata = true;
+//! c.set_export_options(options);
+//!
+//! #[cfg(feature = "png")]
+//! {
+//!     c.draw();
+//!
+//!     let png_bytes = c.export_image(ImageFormat::PngLinear16Bpp).unwrap();
+//!
+//!     // Parse the embedded metadata back.
+//!     let meta = ImageMetadata::read_png(&png_bytes[..]).unwrap();
+//!     assert_eq!(meta.spots.len(), 1);
+//! }
 //! ```
 
+mod color;
 mod draw;
 mod export;
 mod gamma;
+mod index;
 mod pattern;
+mod pixel;
+mod truth;
+
+pub use crate::color::{BayerPattern, ColorMode, SpotColor};
+pub use crate::export::{
+    Binning, EncoderError, ExportOptions, ImageFormat, PngCompression, PngFilter, SerWriter,
+    Window, WindowSpans, Y4mChroma, Y4mWriter,
+};
+pub use crate::pattern::PatternInterpolation;
+pub use crate::pixel::CanvasPixel;
+pub use crate::truth::{GroundTruthFormat, ImageMetadata, SpotInfo};
 
-pub use crate::export::{EncoderError, ImageFormat, Window, WindowSpans};
+#[cfg(feature = "png")]
+pub use crate::export::ApngWriter;
 
+use std::alloc::Layout;
+
+use crate::draw::BoundingBox;
 use crate::gamma::GammaCurve8;
+use crate::index::SpotIndex;
 use crate::pattern::AiryPattern;
 
-/// Image pixel value type: 16-bit pixels
+/// Default image pixel value type: 16-bit pixels
 pub type Pixel = u16;
 
 /// 2D point coordinates: `(X, Y)`
@@ -351,12 +917,19 @@ struct SpotRec {
 
     /// Inverted spot shape matrix (cached)
     shape_inv: SpotShape,
+
+    /// Spot color channel weights
+    color: SpotColor,
 }
 
 /// Opaque light spots drawing canvas object
 ///
 /// Generates the synthesized image containing multiple light spots.
 ///
+/// The canvas pixel sample type `P` defaults to the 16-bit `Pixel` type.
+/// Other pixel types implementing the `CanvasPixel` trait can be used
+/// by creating the canvas with `Canvas::with_dimensions()`.
+///
 /// Basic operations
 /// ----------------
 ///
@@ -389,7 +962,7 @@ struct SpotRec {
 /// let (image_width, image_height) = c.dimensions();
 /// let val_x_y = image_pixbuf[(y * image_width + x) as usize];
 /// ```
-pub struct Canvas {
+pub struct Canvas<P: CanvasPixel = Pixel> {
     /// Canvas width in pixels
     width: u32,
 
@@ -397,7 +970,7 @@ pub struct Canvas {
     height: u32,
 
     /// Background light level
-    background: Pixel,
+    background: P,
 
     /// Light spot draw list
     spots: Vec<SpotRec>,
@@ -408,16 +981,68 @@ pub struct Canvas {
     /// Global spot brightness factor
     brightness: f32,
 
+    /// Color rendering mode
+    color_mode: ColorMode,
+
+    /// Pixel buffer row stride in samples
+    stride: usize,
+
     /// Image pixel buffer
-    pixbuf: Vec<Pixel>,
+    pixbuf: Vec<P>,
+
+    /// Floating point light spot sum buffer (empty if disabled)
+    ///
+    /// Contains the HDR image samples and the accumulated samples
+    /// quantized into the pixel buffer.
+    hdrbuf: Vec<f32>,
+
+    /// HDR image rendering flag
+    hdr: bool,
+
+    /// Accumulated sample quantization full scale level (`None` if disabled)
+    full_scale: Option<f32>,
+
+    /// External frame buffer light spot sum scratch buffer
+    scratch: Vec<f32>,
+
+    /// Dirty canvas regions to redraw (`None` if a full redraw is required)
+    dirty: Option<Vec<BoundingBox>>,
+
+    /// Light spot spatial index (`None` if disabled)
+    index: Option<SpotIndex>,
 
     /// Spot pattern lookup table
     pattern: AiryPattern,
 
-    /// sRBG compression gamma curve LUT
-    gamma_curve: GammaCurve8,
+    /// sRBG compression gamma curve LUT
+    gamma_curve: GammaCurve8,
+
+    /// Image export options
+    export_options: ExportOptions,
+}
+
+/// Frame buffer drawing error type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DrawError {
+    /// Frame buffer row stride is zero or shorter than the row length
+    InvalidStride,
+    /// Frame buffer is too small for the frame dimensions
+    BufferTooSmall,
+    /// Window rectangle does not fit into the canvas
+    BrokenWindow,
+    /// Requested drawing parameters are invalid
+    InvalidParameters,
+}
+
+impl std::fmt::Display for DrawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
 }
 
+impl std::error::Error for DrawError {}
+
 impl Default for SpotShape {
     fn default() -> Self {
         SpotShape {
@@ -671,16 +1296,35 @@ impl Transform {
 }
 
 impl Canvas {
-    /// Creates a new clear canvas to render light spots on.
+    /// Creates a new clear canvas with the default 16-bit pixel type
+    /// to render light spots on.
     #[must_use]
     pub fn new(width: u32, height: u32) -> Self {
-        let background = 0;
+        Canvas::with_dimensions(width, height)
+    }
+}
+
+impl<P: CanvasPixel> Canvas<P> {
+    /// Creates a new clear canvas with the pixel type `P`
+    /// to render light spots on.
+    #[must_use]
+    pub fn with_dimensions(width: u32, height: u32) -> Self {
+        let background = P::default();
         let spots = Vec::with_capacity(8);
         let transform = Transform::default();
         let brightness = 1.0;
-        let pixbuf = vec![0; (width * height) as usize];
+        let color_mode = ColorMode::default();
+        let stride = width as usize;
+        let pixbuf = vec![P::default(); stride * height as usize];
+        let hdrbuf = Vec::new();
+        let hdr = false;
+        let full_scale = None;
+        let scratch = Vec::new();
+        let dirty = None;
+        let index = None;
         let pattern = AiryPattern::new();
         let gamma_curve = GammaCurve8::new();
+        let export_options = ExportOptions::default();
 
         Canvas {
             width,
@@ -689,9 +1333,18 @@ impl Canvas {
             spots,
             transform,
             brightness,
+            color_mode,
+            stride,
             pixbuf,
+            hdrbuf,
+            hdr,
+            full_scale,
+            scratch,
+            dirty,
+            index,
             pattern,
             gamma_curve,
+            export_options,
         }
     }
 
@@ -700,6 +1353,7 @@ impl Canvas {
         // Initialize with the defaults
         let offset = (0.0, 0.0);
         let illumination = 1.0;
+        let color = SpotColor::default();
 
         // Pre-compute and cache the inverted spot shape matrix
         // used by the rasterizer code.
@@ -712,10 +1366,17 @@ impl Canvas {
             illumination,
             shape,
             shape_inv,
+            color,
         };
 
         let id = self.spots.len();
         self.spots.push(spot);
+
+        if let Some(index) = &mut self.index {
+            index.insert(id, position, &shape);
+        }
+
+        self.mark_spot_dirty(id);
         id
     }
 
@@ -751,9 +1412,19 @@ impl Canvas {
     /// The position offset vector is added to the immutable spot position
     /// to calculate the spot rendering coordinates on the canvas.
     pub fn set_spot_offset(&mut self, spot: SpotId, offset: Vector) {
+        // Both the old and the new spot regions are redrawn.
+        self.mark_spot_dirty(spot);
+
         if let Some(s) = self.spots.get_mut(spot) {
             s.offset = offset;
+
+            if let Some(index) = &mut self.index {
+                let world_pos = ((s.position.0 + offset.0), (s.position.1 + offset.1));
+                index.update(spot, world_pos);
+            }
         }
+
+        self.mark_spot_dirty(spot);
     }
 
     /// Sets the internal light spot illumination state.
@@ -764,34 +1435,351 @@ impl Canvas {
         if let Some(s) = self.spots.get_mut(spot) {
             s.illumination = illumination;
         }
+
+        self.mark_spot_dirty(spot);
+    }
+
+    /// Returns the light spot color channel weights.
+    #[must_use]
+    pub fn spot_color(&self, spot: SpotId) -> Option<SpotColor> {
+        self.spots.get(spot).map(|s| s.color)
+    }
+
+    /// Sets the light spot color channel weights.
+    ///
+    /// The spot color weights are multiplied with the effective peak intensity
+    /// to render the color channel samples in the color rendering modes.
+    pub fn set_spot_color(&mut self, spot: SpotId, color: SpotColor) {
+        if let Some(s) = self.spots.get_mut(spot) {
+            s.color = color;
+        }
+
+        self.mark_spot_dirty(spot);
     }
 
     /// Clears the canvas image (fills with background pixels).
     pub fn clear(&mut self) {
         self.pixbuf.fill(self.background);
+        self.hdrbuf.fill(self.background.to_level() / P::FULL_SCALE);
+        self.dirty = None;
     }
 
     /// Draws the light spots onto the canvas image.
+    ///
+    /// The light spots are drawn in parallel if the `rayon` feature is enabled.
     pub fn draw(&mut self) {
         // Always clear the canvas first to avoid unintended overdraw.
         self.clear();
+        self.dirty = Some(Vec::new());
 
         if self.brightness <= 0.0 {
             return;
         }
 
+        #[cfg(feature = "rayon")]
+        self.draw_spots_parallel();
+
         // `self.spots` can not be borrowed for `draw_spot()`
-        for spot_id in 0..self.spots.len() {
+        #[cfg(not(feature = "rayon"))]
+        for spot_id in self.region_spots(&Window::new(self.width, self.height).into()) {
             self.draw_spot(spot_id);
         }
+
+        self.quantize_accumulated();
+    }
+
+    /// Redraws the canvas image regions changed since the last
+    /// `draw()` or `redraw()` call.
+    ///
+    /// The light spot additions and the spot offset, illumination and color
+    /// changes mark the old and the new spot bounding boxes as dirty.
+    /// Only the dirty regions are cleared and redrawn, the resulting image
+    /// is identical to the full `draw()` output.
+    ///
+    /// Falls back to the full `draw()` if the canvas has not been drawn yet,
+    /// the global canvas parameters have changed or too many regions
+    /// are dirty.
+    pub fn redraw(&mut self) {
+        let Some(regions) = self.dirty.replace(Vec::new()) else {
+            self.draw();
+            return;
+        };
+
+        self.draw_regions(&regions);
+    }
+
+    /// Draws the light spots onto the canvas window region only.
+    ///
+    /// Only the light spots intersecting the window rectangle are rasterized
+    /// and only the window region pixels are written, so the drawing cost
+    /// scales with the window size rather than with the canvas size.
+    /// The window region pixels are identical to the full `draw()` output.
+    ///
+    /// The canvas pixels outside of the window are left intact,
+    /// so the next `redraw()` call redraws the whole canvas.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DrawError::BrokenWindow`] if the window rectangle
+    /// does not fit into the canvas.
+    pub fn draw_window(&mut self, window: Window) -> Result<(), DrawError> {
+        if !window.is_inside(self.width, self.height) {
+            return Err(DrawError::BrokenWindow);
+        }
+
+        self.draw_regions(&[window.into()]);
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Draws the light spots into an external frame buffer.
+    ///
+    /// The frame buffer contains `height` rows of `width` pixels
+    /// with `stride` samples from one row start to the next one.
+    /// In the RGB color mode each pixel contains 3 interleaved color channel
+    /// samples. The row padding samples are left intact.
+    ///
+    /// The light spot canvas coordinates are used as the frame buffer
+    /// coordinates and the light spots are clipped to the frame dimensions.
+    /// The light spots are composed the same way as by `draw()`,
+    /// including the floating point accumulation and the spatial index
+    /// culling if enabled. The canvas image buffers are left intact.
+    ///
+    /// The floating point accumulation uses a scratch buffer owned
+    /// by the canvas, which is reused by the subsequent calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DrawError::InvalidStride`] if the row stride is zero
+    /// or less than the row length in samples.
+    ///
+    /// Returns [`DrawError::BufferTooSmall`] if the frame buffer
+    /// does not fit the frame dimensions.
+    pub fn draw_into(
+        &mut self,
+        buf: &mut [P],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<(), DrawError> {
+        let row_len = width as usize * self.color_mode.channels();
+
+        if stride == 0 || stride < row_len {
+            return Err(DrawError::InvalidStride);
+        }
+
+        // The last row may be shorter than the stride.
+        let len = (height as usize).saturating_sub(1) * stride + row_len;
+
+        if height > 0 && buf.len() < len {
+            return Err(DrawError::BufferTooSmall);
+        }
+
+        for row in buf.chunks_mut(stride).take(height as usize) {
+            row[..row_len].fill(self.background);
+        }
+
+        if self.brightness <= 0.0 {
+            return Ok(());
+        }
+
+        self.draw_spots_into(buf, (width, height), stride);
+
+        Ok(())
     }
 
     /// Returns the rendered image pixels buffer.
+    ///
+    /// In the RGB color mode the pixel buffer contains
+    /// interleaved R, G, B color channel samples.
+    ///
+    /// The pixel buffer rows are `row_stride()` samples apart.
     #[must_use]
-    pub fn pixels(&self) -> &[Pixel] {
+    pub fn pixels(&self) -> &[P] {
         &self.pixbuf
     }
 
+    /// Returns the floating point accumulation quantization full scale level.
+    ///
+    /// Returns `None` if the floating point accumulation is disabled.
+    #[must_use]
+    pub fn float_accumulation(&self) -> Option<f32> {
+        self.full_scale
+    }
+
+    /// Enables or disables the floating point light spot accumulation.
+    ///
+    /// When enabled with `Some(full_scale)`, the light spot contributions
+    /// are summed in a floating point buffer and quantized into the pixel
+    /// buffer with rounding at the end of `draw()`. The floating point buffer
+    /// is shared with the HDR rendering. The unit spot intensity
+    /// is mapped to the `full_scale` pixel value above the background level,
+    /// the quantized integer pixel values saturate at the maximum value.
+    ///
+    /// Pass `None` to disable the floating point accumulation.
+    pub fn set_float_accumulation(&mut self, full_scale: Option<f32>) {
+        self.full_scale = full_scale;
+        self.update_float_buffer();
+    }
+
+    /// Returns the light spot spatial index grid cell size.
+    ///
+    /// Returns `None` if the spatial index is disabled.
+    #[must_use]
+    pub fn spatial_index(&self) -> Option<f32> {
+        self.index.as_ref().map(SpotIndex::cell_size)
+    }
+
+    /// Enables or disables the light spot spatial index.
+    ///
+    /// When enabled with `Some(cell_size)`, the light spot world positions
+    /// are indexed by a uniform grid with the `cell_size` world coordinate
+    /// units cells. The spots outside of the drawn canvas region
+    /// are culled using the index instead of calculating their bounding boxes.
+    /// The cell size should be comparable to the world size of the canvas
+    /// region containing a few light spots.
+    ///
+    /// Pass `None` to disable the spatial index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DrawError::InvalidParameters`] if `cell_size`
+    /// is not a finite positive number.
+    pub fn set_spatial_index(&mut self, cell_size: Option<f32>) -> Result<(), DrawError> {
+        if cell_size.is_some_and(|size| !size.is_finite() || size <= 0.0) {
+            return Err(DrawError::InvalidParameters);
+        }
+
+        self.index = cell_size.map(|size| {
+            let mut index = SpotIndex::new(size);
+
+            for (id, s) in self.spots.iter().enumerate() {
+                let world_pos = ((s.position.0 + s.offset.0), (s.position.1 + s.offset.1));
+                index.insert(id, world_pos, &s.shape);
+            }
+
+            index
+        });
+
+        Ok(())
+    }
+
+    /// Returns the light spots with the bounding boxes intersecting
+    /// the canvas window rectangle in the draw list order.
+    ///
+    /// Uses the spatial index to cull the spots outside of the window
+    /// if enabled.
+    #[must_use]
+    pub fn spots_in_window(&self, window: Window) -> Vec<SpotId> {
+        let region = BoundingBox::from(window);
+
+        self.region_spots(&region)
+            .into_iter()
+            .filter(|&id| self.is_spot_in_region(id, &region))
+            .collect()
+    }
+
+    /// Returns the rendered floating point HDR image buffer.
+    ///
+    /// The HDR samples are normalized to the pixel type full scale,
+    /// so the overexposed samples have values above 1.0.
+    ///
+    /// Returns `None` if the HDR rendering is disabled.
+    #[must_use]
+    pub fn hdr_pixels(&self) -> Option<&[f32]> {
+        if self.hdr {
+            Some(&self.hdrbuf)
+        } else {
+            None
+        }
+    }
+
+    /// Enables or disables the floating point HDR rendering.
+    ///
+    /// The HDR image buffer is rendered along with the pixel buffer
+    /// without numeric saturation. Enabling the HDR rendering takes effect
+    /// on the next `draw()` call.
+    pub fn set_hdr_rendering(&mut self, enabled: bool) {
+        self.hdr = enabled;
+        self.update_float_buffer();
+    }
+
+    /// Returns the pixel buffer row stride in samples.
+    ///
+    /// Defaults to the tightly packed row length (canvas width times
+    /// the number of color channels per pixel).
+    #[must_use]
+    pub fn row_stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Sets the pixel buffer row stride in samples.
+    ///
+    /// The row padding samples are filled with the background level
+    /// and are never exported. The pixel buffer is reallocated and zeroed
+    /// if the row stride changes, the new layout takes effect
+    /// on the next `draw()` call.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DrawError::InvalidStride`] if the row stride is zero,
+    /// less than the row length in samples or the resulting pixel buffer
+    /// size overflows.
+    pub fn set_row_stride(&mut self, stride: usize) -> Result<(), DrawError> {
+        if stride == 0 || stride < self.row_len(self.color_mode) {
+            return Err(DrawError::InvalidStride);
+        }
+
+        // Both the pixel buffer and the floating point buffer must fit
+        // into the address space.
+        let len = stride
+            .checked_mul(self.height as usize)
+            .ok_or(DrawError::InvalidStride)?;
+
+        if Layout::array::<P>(len).is_err() || Layout::array::<f32>(len).is_err() {
+            return Err(DrawError::InvalidStride);
+        }
+
+        if stride != self.stride {
+            self.stride = stride;
+            self.realloc_buffers();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the row length in samples for the color mode.
+    fn row_len(&self, mode: ColorMode) -> usize {
+        self.width as usize * mode.channels()
+    }
+
+    /// Reallocates and zeroes the pixel buffer along with
+    /// the floating point buffer if enabled.
+    fn realloc_buffers(&mut self) {
+        self.dirty = None;
+
+        let len = self.stride * self.height as usize;
+        self.pixbuf = vec![P::default(); len];
+
+        if !self.hdrbuf.is_empty() {
+            self.hdrbuf = vec![0.0; len];
+        }
+    }
+
+    /// Allocates the floating point light spot sum buffer if the HDR
+    /// rendering or the floating point accumulation is enabled,
+    /// otherwise releases it.
+    fn update_float_buffer(&mut self) {
+        self.dirty = None;
+
+        if !self.hdr && self.full_scale.is_none() {
+            self.hdrbuf = Vec::new();
+        } else if self.hdrbuf.is_empty() {
+            self.hdrbuf = vec![0.0; self.pixbuf.len()];
+        }
+    }
+
     /// Returns the canvas dimensions as `(width, height)`.
     #[must_use]
     pub fn dimensions(&self) -> (u32, u32) {
@@ -799,8 +1787,9 @@ impl Canvas {
     }
 
     /// Sets the background light level (dark pixel value).
-    pub fn set_background(&mut self, level: Pixel) {
+    pub fn set_background(&mut self, level: P) {
         self.background = level;
+        self.dirty = None;
     }
 
     /// Sets the world coordinates to canvas coordinates transformation.
@@ -808,11 +1797,90 @@ impl Canvas {
     /// The light spot coordinates are defined in the world coordinate system only.
     pub fn set_view_transform(&mut self, transform: Transform) {
         self.transform = transform;
+        self.dirty = None;
     }
 
     /// Sets the global brightness level (light spot intensity adjustment).
     pub fn set_brightness(&mut self, brightness: f32) {
         self.brightness = brightness;
+        self.dirty = None;
+    }
+
+    /// Returns the light spot pattern LUT size and interpolation mode.
+    #[must_use]
+    pub fn pattern_lut(&self) -> (usize, PatternInterpolation) {
+        (self.pattern.lut_size(), self.pattern.interpolation())
+    }
+
+    /// Sets the light spot pattern LUT size and interpolation mode.
+    ///
+    /// The default pattern LUT contains 1024 samples looked up without
+    /// interpolation. Larger LUT sizes and the linear interpolation reduce
+    /// the stair-stepping artifacts in the profiles of large light spots.
+    /// The LUT size must be in the range from 16 to 1048576 samples.
+    ///
+    /// The new pattern LUT takes effect on the next `draw()` call.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DrawError::InvalidParameters`] if the LUT size
+    /// is out of the supported range.
+    pub fn set_pattern_lut(
+        &mut self,
+        lut_size: usize,
+        interpolation: PatternInterpolation,
+    ) -> Result<(), DrawError> {
+        if !(AiryPattern::MIN_LUT_SIZE..=AiryPattern::MAX_LUT_SIZE).contains(&lut_size) {
+            return Err(DrawError::InvalidParameters);
+        }
+
+        self.pattern = AiryPattern::with_params(lut_size, interpolation);
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Returns the canvas color rendering mode.
+    #[must_use]
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// Sets the canvas color rendering mode.
+    ///
+    /// The new color mode takes effect on the next `draw()` call.
+    /// The pixel buffer is reallocated and zeroed if the number
+    /// of color channels per pixel changes.
+    ///
+    /// The tightly packed row stride follows the new row length.
+    /// A custom row stride is preserved if it fits the new row length,
+    /// otherwise the row stride is reset to the tightly packed one.
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        if mode.channels() != self.color_mode.channels() {
+            let row_len = self.row_len(mode);
+
+            if self.stride == self.row_len(self.color_mode) || self.stride < row_len {
+                self.stride = row_len;
+            }
+
+            self.realloc_buffers();
+        }
+
+        self.color_mode = mode;
+        self.dirty = None;
+    }
+
+    /// Returns the image export options.
+    #[must_use]
+    pub fn export_options(&self) -> ExportOptions {
+        self.export_options
+    }
+
+    /// Sets the image export options.
+    ///
+    /// The export options are applied to all subsequent image exports.
+    pub fn set_export_options(&mut self, options: ExportOptions) {
+        self.export_options = options;
     }
 }
 
@@ -898,6 +1966,70 @@ mod tests {
         assert_eq!(c.pixels()[0], 200);
     }
 
+    #[test]
+    fn set_color_modes() {
+        let mut c = Canvas::new(16, 16);
+
+        c.set_color_mode(ColorMode::Bayer(BayerPattern::Rggb));
+        assert_eq!(c.pixels().len(), 16 * 16);
+
+        c.set_color_mode(ColorMode::Rgb);
+        assert_eq!(c.color_mode(), ColorMode::Rgb);
+        assert_eq!(c.pixels().len(), 3 * 16 * 16);
+
+        c.set_background(300);
+        c.draw();
+        assert_eq!(c.pixels()[3 * 16 * 16 - 1], 300);
+
+        c.set_color_mode(ColorMode::Monochrome);
+        assert_eq!(c.pixels().len(), 16 * 16);
+    }
+
+    #[test]
+    fn set_row_strides() {
+        let mut c = Canvas::new(30, 16);
+        assert_eq!(c.row_stride(), 30);
+
+        assert_eq!(c.set_row_stride(0), Err(DrawError::InvalidStride));
+        assert_eq!(c.set_row_stride(29), Err(DrawError::InvalidStride));
+
+        c.set_row_stride(32).unwrap();
+        assert_eq!(c.row_stride(), 32);
+        assert_eq!(c.pixels().len(), 32 * 16);
+
+        c.set_background(300);
+        c.draw();
+        assert_eq!(c.pixels()[31], 300);
+
+        // The custom stride is too short for the RGB rows.
+        c.set_color_mode(ColorMode::Rgb);
+        assert_eq!(c.row_stride(), 3 * 30);
+        assert_eq!(c.set_row_stride(64), Err(DrawError::InvalidStride));
+
+        c.set_row_stride(96).unwrap();
+        c.set_color_mode(ColorMode::Monochrome);
+        assert_eq!(c.row_stride(), 96);
+        assert_eq!(c.pixels().len(), 96 * 16);
+
+        c.set_color_mode(ColorMode::Bayer(BayerPattern::Grbg));
+        c.set_row_stride(30).unwrap();
+        c.set_color_mode(ColorMode::Rgb);
+        assert_eq!(c.row_stride(), 3 * 30);
+
+        // The pixel buffer size overflows.
+        let mut c = Canvas::new(4, 4);
+        assert_eq!(
+            c.set_row_stride(usize::MAX / 2),
+            Err(DrawError::InvalidStride)
+        );
+        assert_eq!(
+            c.set_row_stride(usize::MAX / 8),
+            Err(DrawError::InvalidStride)
+        );
+        assert_eq!(c.row_stride(), 4);
+        assert_eq!(c.pixels().len(), 4 * 4);
+    }
+
     #[test]
     fn move_spots() {
         let shape = SpotShape::default();
@@ -1012,4 +2144,284 @@ mod tests {
         // new spot3 location
         assert_eq!(c.pixels()[32 * 31 + 3], 27742);
     }
+
+    #[test]
+    fn draw_into_buffer() {
+        let shape = SpotShape::default().scale(2.0);
+        let mut c = Canvas::new(16, 8);
+        c.set_background(1000);
+
+        c.add_spot((3.3, 4.6), shape, 0.7);
+        c.add_spot((14.5, 2.2), shape, 0.9);
+        c.draw();
+
+        // Frame buffer rows padded to 20 pixels
+        let mut buf = vec![7u16; 20 * 7 + 16];
+        c.draw_into(&mut buf, 16, 8, 20).unwrap();
+
+        for (row, span) in buf.chunks(20).zip(c.pixels().chunks(16)) {
+            assert_eq!(row[..16], *span);
+            assert!(row[16..].iter().all(|&p| p == 7));
+        }
+
+        // Draw into a smaller RGB frame buffer.
+        c.set_color_mode(ColorMode::Rgb);
+        c.draw();
+
+        let mut buf = vec![0u16; 3 * 8 * 4];
+        c.draw_into(&mut buf, 8, 4, 3 * 8).unwrap();
+
+        for (row, span) in buf.chunks(3 * 8).zip(c.pixels().chunks(3 * 16)) {
+            assert_eq!(row, &span[..3 * 8]);
+        }
+    }
+
+    #[test]
+    fn draw_into_accumulated() {
+        let shape = SpotShape::default().scale(1.5);
+        let mut c = Canvas::new(24, 16);
+        c.set_background(100);
+        c.set_float_accumulation(Some(1.0));
+        c.set_spatial_index(Some(8.0)).unwrap();
+
+        // Faint overlapping spots sum up before the quantization.
+        for i in 0..50 {
+            c.add_spot((6.0 + (i % 5) as f32 * 0.1, 7.0), shape, 0.002);
+        }
+
+        c.add_spot((18.5, 9.2), shape, 0.7);
+        c.add_spot((120.0, -40.0), shape, 1.0);
+        c.draw();
+
+        let mut buf = vec![0u16; 24 * 16];
+        c.draw_into(&mut buf, 24, 16, 24).unwrap();
+        assert_eq!(buf, c.pixels());
+
+        // The scratch buffer is reused for the same frame size.
+        let scratch = c.scratch.as_ptr();
+        c.draw_into(&mut buf, 24, 16, 24).unwrap();
+        assert_eq!(c.scratch.as_ptr(), scratch);
+        assert_eq!(buf, c.pixels());
+
+        // The per-spot quantization gives different pixel values.
+        let pixels = c.pixels().to_vec();
+        c.set_float_accumulation(None);
+        c.draw_into(&mut buf, 24, 16, 24).unwrap();
+        assert_ne!(buf, pixels);
+        c.set_float_accumulation(Some(1.0));
+
+        // Padded rows of a smaller frame buffer
+        let mut buf = vec![7u16; 30 * 11 + 20];
+        c.draw_into(&mut buf, 20, 12, 30).unwrap();
+
+        for (row, span) in buf.chunks(30).zip(pixels.chunks(24)) {
+            assert_eq!(row[..20], span[..20]);
+            assert!(row[20..].iter().all(|&p| p == 7));
+        }
+    }
+
+    #[test]
+    fn draw_into_errors() {
+        let mut c = Canvas::new(16, 8);
+        let mut buf = vec![0u16; 16 * 8];
+
+        assert_eq!(
+            c.draw_into(&mut buf, 16, 8, 15),
+            Err(DrawError::InvalidStride)
+        );
+        assert_eq!(
+            c.draw_into(&mut buf, 0, 8, 0),
+            Err(DrawError::InvalidStride)
+        );
+        assert_eq!(
+            c.draw_into(&mut buf, 16, 8, 17),
+            Err(DrawError::BufferTooSmall)
+        );
+        assert_eq!(
+            c.draw_into(&mut buf[..16 * 7], 16, 8, 16),
+            Err(DrawError::BufferTooSmall)
+        );
+        assert_eq!(c.draw_into(&mut buf, 16, 0, 16), Ok(()));
+
+        // The drawing errors are standard error types.
+        let err: Box<dyn std::error::Error> = DrawError::BufferTooSmall.into();
+        assert_eq!(err.to_string(), "BufferTooSmall");
+    }
+
+    #[test]
+    fn spatial_index() {
+        let mut c = Canvas::new(200, 150);
+        c.set_view_transform(
+            Transform::default()
+                .translate((-300.0, -200.0))
+                .rotate(20.0)
+                .scale(0.25)
+                .translate((100.0, 75.0)),
+        );
+
+        // Wide-field spot catalog mostly outside of the view
+        let mut seed = 54321u32;
+        let mut rand = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+
+        for _ in 0..2000 {
+            let position = (rand() * 2000.0 - 700.0, rand() * 1500.0 - 550.0);
+            let shape = SpotShape::default().scale(1.0 + 3.0 * rand());
+            c.add_spot(position, shape, 0.2 + rand());
+        }
+
+        assert_eq!(c.spatial_index(), None);
+
+        c.draw();
+        let pixels = c.pixels().to_vec();
+
+        let wnd = Window::new(60, 40).at(70, 50);
+        let spots = c.spots_in_window(wnd);
+        assert!(!spots.is_empty());
+
+        c.set_spatial_index(Some(50.0)).unwrap();
+        assert_eq!(c.spatial_index(), Some(50.0));
+
+        // Most of the off-canvas spots are culled.
+        let culled = c.region_spots(&Window::new(200, 150).into());
+        assert!(culled.len() < 1000, "{}", culled.len());
+
+        c.draw();
+        assert_eq!(c.pixels(), pixels);
+        assert_eq!(c.spots_in_window(wnd), spots);
+
+        // The spots added and moved after the index is built
+        let spot = c.add_spot((350.0, 210.0), SpotShape::default().scale(8.0), 0.9);
+        c.set_spot_offset(7, (120.0, -40.0));
+        c.set_spot_offset(spot, (-30.0, 25.0));
+
+        c.redraw();
+        let pixels = c.pixels().to_vec();
+        let spots = c.spots_in_window(wnd);
+
+        c.set_spatial_index(None).unwrap();
+        c.draw();
+        assert_eq!(c.pixels(), pixels);
+        assert_eq!(c.spots_in_window(wnd), spots);
+
+        let all = (0..c.spots.len()).filter(|&id| c.is_spot_visible(id));
+        assert!(c.spots_in_window(Window::new(200, 150)).into_iter().eq(all));
+    }
+
+    #[test]
+    fn spatial_index_negative_cell() {
+        let mut c = Canvas::new(16, 16);
+        c.add_spot((8.0, 8.0), SpotShape::default(), 1.0);
+
+        assert_eq!(
+            c.set_spatial_index(Some(-10.0)),
+            Err(DrawError::InvalidParameters)
+        );
+        assert_eq!(
+            c.set_spatial_index(Some(0.0)),
+            Err(DrawError::InvalidParameters)
+        );
+        assert!(c.spatial_index().is_none());
+    }
+
+    #[test]
+    fn spatial_index_nan_cell() {
+        let mut c = Canvas::new(16, 16);
+        c.set_spatial_index(Some(32.0)).unwrap();
+
+        assert_eq!(
+            c.set_spatial_index(Some(f32::NAN)),
+            Err(DrawError::InvalidParameters)
+        );
+        assert_eq!(
+            c.set_spatial_index(Some(f32::INFINITY)),
+            Err(DrawError::InvalidParameters)
+        );
+        assert_eq!(c.spatial_index(), Some(32.0));
+    }
+
+    #[test]
+    fn set_pattern_luts() {
+        let mut c = Canvas::new(64, 64);
+        c.add_spot((32.3, 31.6), SpotShape::default().scale(20.0), 0.9);
+
+        assert_eq!(c.pattern_lut(), (1024, PatternInterpolation::Nearest));
+
+        c.draw();
+        let nearest = c.pixels().to_vec();
+
+        c.set_pattern_lut(4096, PatternInterpolation::Linear)
+            .unwrap();
+        assert_eq!(c.pattern_lut(), (4096, PatternInterpolation::Linear));
+
+        // The new pattern LUT requires a full redraw.
+        c.redraw();
+        let linear = c.pixels().to_vec();
+        assert_ne!(linear, nearest);
+
+        // The sample values are close.
+        for (a, b) in linear.iter().zip(&nearest) {
+            assert!(a.abs_diff(*b) < 150, "{a} != {b}");
+        }
+
+        c.set_pattern_lut(1024, PatternInterpolation::Nearest)
+            .unwrap();
+        c.draw();
+        assert_eq!(c.pixels(), nearest);
+
+        // Out of range LUT sizes are rejected.
+        for size in [0, 15, (1 << 20) + 1, usize::MAX] {
+            assert_eq!(
+                c.set_pattern_lut(size, PatternInterpolation::Linear),
+                Err(DrawError::InvalidParameters)
+            );
+        }
+
+        assert_eq!(c.pattern_lut(), (1024, PatternInterpolation::Nearest));
+        c.set_pattern_lut(16, PatternInterpolation::Linear).unwrap();
+        c.set_pattern_lut(1 << 20, PatternInterpolation::Linear)
+            .unwrap();
+    }
+
+    #[test]
+    fn draw_windows() {
+        let mut c = Canvas::new(64, 48);
+        c.set_background(100);
+
+        c.add_spot((20.3, 15.6), SpotShape::default().scale(4.0), 0.8);
+        c.add_spot((30.8, 20.1), SpotShape::default().scale(2.0), 0.6);
+        c.add_spot((55.0, 40.0), SpotShape::default(), 0.9);
+
+        c.draw();
+        let full = c.pixels().to_vec();
+
+        // Clear the canvas with a different background.
+        c.set_background(0);
+        c.clear();
+        c.set_background(100);
+
+        let wnd = Window::new(20, 10).at(16, 12);
+        c.draw_window(wnd).unwrap();
+
+        for (i, (&p, &f)) in c.pixels().iter().zip(&full).enumerate() {
+            let (x, y) = ((i % 64) as u32, (i / 64) as u32);
+
+            if (16..36).contains(&x) && (12..22).contains(&y) {
+                assert_eq!(p, f);
+            } else {
+                assert_eq!(p, 0);
+            }
+        }
+
+        assert_eq!(
+            c.draw_window(Window::new(20, 10).at(50, 12)),
+            Err(DrawError::BrokenWindow)
+        );
+
+        // The rest of the canvas is redrawn.
+        c.redraw();
+        assert_eq!(c.pixels(), full);
+    }
 }
@@ -7,20 +7,48 @@
 //! Defines a new opaque private structure `AiryPattern`
 //! implementing the intensity function of the Airy disc
 //! diffraction pattern as a linear LUT.
+//!
+//! Defines the public LUT interpolation mode enum `PatternInterpolation`.
 
 // Bessel function of the first kind of order one aka `J1(x)`
 use libm::j1f;
 
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m128, __m128i, _mm_add_epi32, _mm_add_ps, _mm_cvtepi32_ps, _mm_cvttps_epi32, _mm_min_ps,
+    _mm_mul_ps, _mm_set1_epi32, _mm_set1_ps, _mm_setr_ps, _mm_storeu_si128, _mm_sub_ps,
+};
+
 /// First positive zero of `J1(x)`
 const J1_ZERO1: f32 = 3.831_706;
 
 /// Second positive zero of `J1(x)`
 const J1_ZERO2: f32 = 7.015_587;
 
+/// Light spot pattern LUT interpolation modes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PatternInterpolation {
+    /// Nearest LUT sample lookup (default)
+    #[default]
+    Nearest,
+    /// Linear interpolation between the adjacent LUT samples
+    Linear,
+}
+
 /// Opaque Airy pattern function LUT object
 pub(crate) struct AiryPattern {
     /// LUT samples vector
     lut: Vec<f32>,
+
+    /// Airy intensity pattern LUT size
+    lut_size: usize,
+
+    /// LUT index to function argument ratio
+    index_scale: f32,
+
+    /// LUT interpolation mode
+    interpolation: PatternInterpolation,
 }
 
 #[allow(
@@ -37,34 +65,48 @@ impl AiryPattern {
     /// the radius of the second Airy disc minumum.
     pub(crate) const SIZE_FACTOR: f32 = J1_ZERO2 / J1_ZERO1;
 
-    /// Airy intensity pattern LUT size
-    const LUT_SIZE: usize = 1024;
-
-    /// Pattern LUT zero-padding length
-    const LUT_PADDING_LEN: usize = Self::LUT_SIZE;
+    /// Default Airy intensity pattern LUT size
+    const DEFAULT_LUT_SIZE: usize = 1024;
 
-    /// Airy intensity pattern LUT size (floating point)
-    const LUT_SIZE_FP: f32 = Self::LUT_SIZE as f32;
+    /// Minimum Airy intensity pattern LUT size
+    pub(crate) const MIN_LUT_SIZE: usize = 16;
 
-    /// LUT index to function argument ratio
-    const INDEX_SCALE: f32 = Self::LUT_SIZE_FP / Self::SIZE_FACTOR;
+    /// Maximum Airy intensity pattern LUT size
+    ///
+    /// Limits the LUT memory footprint to 8 MiB and keeps the LUT indices
+    /// exactly representable as `f32` values.
+    pub(crate) const MAX_LUT_SIZE: usize = 1 << 20;
 
-    /// Creates the Airy intensity pattern function LUT.
+    /// Creates the default Airy intensity pattern function LUT.
     #[must_use]
     pub(crate) fn new() -> Self {
+        Self::with_params(Self::DEFAULT_LUT_SIZE, PatternInterpolation::Nearest)
+    }
+
+    /// Creates the Airy intensity pattern function LUT with `lut_size` samples
+    /// and the LUT interpolation mode.
+    ///
+    /// The LUT size is validated by the caller.
+    #[must_use]
+    pub(crate) fn with_params(lut_size: usize, interpolation: PatternInterpolation) -> Self {
+        debug_assert!((Self::MIN_LUT_SIZE..=Self::MAX_LUT_SIZE).contains(&lut_size));
+
+        // Airy intensity pattern LUT size (floating point)
+        let lut_size_fp = lut_size as f32;
+
         let lut_fn = |i| {
             // Resolve singularity at x = 0
             if i == 0 {
                 // J1(x) ~ x/2, x -> 0
                 1.0
-            } else if i >= Self::LUT_SIZE {
+            } else if i >= lut_size {
                 // Clamp to zero for x > J1_ZERO2
                 0.0
             } else {
                 // Evaluate the Airy function:
 
                 // Airy pattern function argument
-                let x = (i as f32) * J1_ZERO2 / Self::LUT_SIZE_FP;
+                let x = (i as f32) * J1_ZERO2 / lut_size_fp;
 
                 // Airy disc pattern intensity distribution
                 let j1nc = 2.0 * j1f(x) / x;
@@ -73,21 +115,110 @@ impl AiryPattern {
         };
 
         // LUT array length: include the table and the zero-padding space
-        let len = Self::LUT_SIZE + Self::LUT_PADDING_LEN;
+        // of the same length.
+        let len = 2 * lut_size;
 
         let lut = (0..len).map(lut_fn).collect();
 
-        AiryPattern { lut }
+        AiryPattern {
+            lut,
+            lut_size,
+            index_scale: lut_size_fp / Self::SIZE_FACTOR,
+            interpolation,
+        }
+    }
+
+    /// Returns the Airy intensity pattern LUT size.
+    #[must_use]
+    pub(crate) fn lut_size(&self) -> usize {
+        self.lut_size
+    }
+
+    /// Returns the LUT interpolation mode.
+    #[must_use]
+    pub(crate) fn interpolation(&self) -> PatternInterpolation {
+        self.interpolation
     }
 
     /// Evaluates the Airy intensity pattern function.
     #[must_use]
     pub(crate) fn eval(&self, x: f32) -> f32 {
-        // Calculate the LUT index with rounding to the nearest integer.
-        let i = (x * Self::INDEX_SCALE + 0.5) as usize;
-
         // Transparently zero-extend the pattern function LUT to infinity.
-        self.lut.get(i).copied().unwrap_or(0.0)
+        let sample = |i: usize| self.lut.get(i).copied().unwrap_or(0.0);
+
+        match self.interpolation {
+            PatternInterpolation::Nearest => {
+                // Calculate the LUT index with rounding to the nearest integer.
+                sample((x * self.index_scale + 0.5) as usize)
+            }
+            PatternInterpolation::Linear => {
+                let pos = x * self.index_scale;
+                let i = pos as usize;
+
+                // The last LUT sample is always zero.
+                if i >= self.lut.len() - 1 {
+                    return 0.0;
+                }
+
+                // Interpolate between the adjacent LUT samples.
+                let (a, b) = (self.lut[i], self.lut[i + 1]);
+                a + (b - a) * (pos - i as f32)
+            }
+        }
+    }
+
+    /// Evaluates the Airy intensity pattern function for four arguments
+    /// using the SSE2 instructions.
+    ///
+    /// The LUT indices are calculated and clamped to the zero-padded LUT tail
+    /// in vector registers, so the LUT samples are gathered without
+    /// the bounds checks. The results are identical to the `eval()` ones
+    /// for the non-negative arguments.
+    #[cfg(target_arch = "x86_64")]
+    #[must_use]
+    pub(crate) fn eval_sse2(&self, x: __m128) -> __m128 {
+        // The last LUT sample is always zero.
+        let last = (self.lut.len() - 1) as f32;
+        let lut = self.lut.as_ptr();
+
+        // SAFETY: SSE2 is always available on x86_64.
+        // The LUT indices are non-negative and do not exceed `last`.
+        unsafe {
+            let gather = |index: __m128i| {
+                let mut i = [0i32; 4];
+                _mm_storeu_si128(i.as_mut_ptr().cast(), index);
+
+                _mm_setr_ps(
+                    *lut.add(i[0] as usize),
+                    *lut.add(i[1] as usize),
+                    *lut.add(i[2] as usize),
+                    *lut.add(i[3] as usize),
+                )
+            };
+
+            let scale = _mm_set1_ps(self.index_scale);
+
+            match self.interpolation {
+                PatternInterpolation::Nearest => {
+                    // Calculate the LUT indices with rounding to the nearest integer.
+                    let pos = _mm_add_ps(_mm_mul_ps(x, scale), _mm_set1_ps(0.5));
+                    let pos = _mm_min_ps(pos, _mm_set1_ps(last));
+
+                    gather(_mm_cvttps_epi32(pos))
+                }
+                PatternInterpolation::Linear => {
+                    let pos = _mm_min_ps(_mm_mul_ps(x, scale), _mm_set1_ps(last - 1.0));
+                    let i = _mm_cvttps_epi32(pos);
+
+                    // Interpolate between the adjacent LUT samples.
+                    let a = gather(i);
+                    let b = gather(_mm_add_epi32(i, _mm_set1_epi32(1)));
+                    let t = _mm_sub_ps(pos, _mm_cvtepi32_ps(i));
+
+                    _mm_add_ps(a, _mm_mul_ps(_mm_sub_ps(b, a), t))
+                }
+            }
+        }
     }
 }
 
@@ -139,4 +270,86 @@ mod tests {
 
         // assert!(false, "T = {:?}", airy.lut)
     }
+
+    /// Evaluates the Airy intensity pattern function directly.
+    fn airy(r: f32) -> f32 {
+        if r == 0.0 {
+            return 1.0;
+        }
+
+        let x = r * J1_ZERO1;
+        let j1nc = 2.0 * j1f(x) / x;
+        j1nc * j1nc
+    }
+
+    /// Calculates the maximum LUT evaluation error against
+    /// the direct evaluation within the effective spot radius.
+    fn max_error(airy_lut: &AiryPattern) -> f32 {
+        const STEPS: u32 = 100_000;
+
+        (0..STEPS)
+            .map(|i| i as f32 * AiryPattern::SIZE_FACTOR / STEPS as f32)
+            .map(|r| (airy_lut.eval(r) - airy(r)).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn lut_errors() {
+        use PatternInterpolation::{Linear, Nearest};
+
+        // Maximum errors of the LUT sizes and interpolation modes
+        let bounds = [
+            (256, Nearest, 7e-3),
+            (256, Linear, 6e-5),
+            (1024, Nearest, 2e-3),
+            (1024, Linear, 4e-6),
+            (4096, Nearest, 5e-4),
+            (4096, Linear, 1e-6),
+        ];
+
+        for (size, mode, bound) in bounds {
+            let airy_lut = AiryPattern::with_params(size, mode);
+            assert_eq!(airy_lut.lut_size(), size);
+            assert_eq!(airy_lut.interpolation(), mode);
+
+            let e = max_error(&airy_lut);
+            assert!(e < bound, "{size} {mode:?}: E = {e}");
+        }
+
+        // The linear interpolation is continuous at the LUT end.
+        let airy_lut = AiryPattern::with_params(1024, Linear);
+        assert_eq!(airy_lut.eval(AiryPattern::SIZE_FACTOR), 0.0);
+        assert_eq!(airy_lut.eval(10.0), 0.0);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn eval_sse2() {
+        use std::arch::x86_64::_mm_storeu_ps;
+        use PatternInterpolation::{Linear, Nearest};
+
+        for (size, mode) in [(16, Nearest), (16, Linear), (1024, Nearest), (1000, Linear)] {
+            let airy_lut = AiryPattern::with_params(size, mode);
+
+            // The arguments span the LUT, the zero-padding and beyond.
+            for i in 0..2000 {
+                let x = [0.0, 0.37, 1.0, 1.83, 3.5, 1e9, f32::INFINITY, 0.0]
+                    .map(|x: f32| x + (i as f32) * 0.003);
+
+                let mut v = [0.0f32; 4];
+
+                for x in x.chunks(4) {
+                    // SAFETY: SSE2 is always available on x86_64.
+                    unsafe {
+                        let xs = _mm_setr_ps(x[0], x[1], x[2], x[3]);
+                        _mm_storeu_ps(v.as_mut_ptr(), airy_lut.eval_sse2(xs));
+                    }
+
+                    for (x, v) in x.iter().zip(v) {
+                        assert_eq!(v, airy_lut.eval(*x), "{size} {mode:?}: x = {x}");
+                    }
+                }
+            }
+        }
+    }
 }
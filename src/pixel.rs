@@ -0,0 +1,217 @@
+//! Planetarium
+//! ===========
+//!
+//! Canvas pixel type definitions
+//! -----------------------------
+//!
+//! Defines the public canvas pixel sample trait `CanvasPixel`
+//! and implements it for `u8`, `u16`, `u32` and `f32`.
+
+use std::fmt::Debug;
+
+/// Canvas pixel sample type
+///
+/// Defines the pixel sample arithmetic used by the light spot rasterizer
+/// and the conversion to the 16-bit linear light samples used by
+/// the image export encoders.
+///
+/// The unit light spot intensity corresponds to the `FULL_SCALE`
+/// pixel level. The integer pixel types saturate at the maximum value,
+/// the floating point pixel type preserves the values above full scale.
+///
+/// Usage
+/// -----
+///
+/// ```
+/// use planetarium::{Canvas, CanvasPixel, SpotShape};
+///
+/// // Draw on a 8-bit canvas.
+/// let mut c = Canvas::<u8>::with_dimensions(64, 64);
+///
+/// c.add_spot((32.0, 32.0), SpotShape::default(), 1.0);
+/// c.draw();
+///
+/// assert_eq!(c.pixels()[32 * 64 + 32], u8::MAX);
+/// assert_eq!(c.pixels()[32 * 64 + 32].to_u16(), u16::MAX);
+/// ```
+pub trait CanvasPixel: Copy + Default + PartialEq + Debug + Send + Sync + 'static {
+    /// Pixel level of the unit light intensity
+    const FULL_SCALE: f32;
+
+    /// Converts the pixel level to the pixel value
+    /// with truncation and saturation.
+    #[must_use]
+    fn from_level(level: f32) -> Self;
+
+    /// Converts the pixel level to the pixel value
+    /// with rounding and saturation.
+    #[must_use]
+    fn quantize(level: f32) -> Self;
+
+    /// Returns the pixel level.
+    #[must_use]
+    fn to_level(self) -> f32;
+
+    /// Adds the pixel values with numeric saturation.
+    #[must_use]
+    fn saturating_add(self, other: Self) -> Self;
+
+    /// Converts the pixel value to a 16-bit linear light sample
+    /// with the same full scale.
+    #[must_use]
+    fn to_u16(self) -> u16;
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+impl CanvasPixel for u8 {
+    const FULL_SCALE: f32 = u8::MAX as f32;
+
+    fn from_level(level: f32) -> Self {
+        // Float to integer casts saturate at the integer type range.
+        level as u8
+    }
+
+    fn quantize(level: f32) -> Self {
+        level.round() as u8
+    }
+
+    fn to_level(self) -> f32 {
+        f32::from(self)
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        u8::saturating_add(self, other)
+    }
+
+    fn to_u16(self) -> u16 {
+        // Replicate the 8-bit value to scale 0xFF to 0xFFFF.
+        u16::from(self) * 0x101
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+impl CanvasPixel for u16 {
+    const FULL_SCALE: f32 = u16::MAX as f32;
+
+    fn from_level(level: f32) -> Self {
+        // Float to integer casts saturate at the integer type range.
+        level as u16
+    }
+
+    fn quantize(level: f32) -> Self {
+        level.round() as u16
+    }
+
+    fn to_level(self) -> f32 {
+        f32::from(self)
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        u16::saturating_add(self, other)
+    }
+
+    fn to_u16(self) -> u16 {
+        self
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+impl CanvasPixel for u32 {
+    const FULL_SCALE: f32 = u32::MAX as f32;
+
+    fn from_level(level: f32) -> Self {
+        // Float to integer casts saturate at the integer type range.
+        level as u32
+    }
+
+    fn quantize(level: f32) -> Self {
+        level.round() as u32
+    }
+
+    fn to_level(self) -> f32 {
+        self as f32
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        u32::saturating_add(self, other)
+    }
+
+    fn to_u16(self) -> u16 {
+        (self >> 16) as u16
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+impl CanvasPixel for f32 {
+    const FULL_SCALE: f32 = 1.0;
+
+    fn from_level(level: f32) -> Self {
+        level
+    }
+
+    fn quantize(level: f32) -> Self {
+        level
+    }
+
+    fn to_level(self) -> f32 {
+        self
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn to_u16(self) -> u16 {
+        // Float to integer casts saturate at the integer type range.
+        (self * f32::from(u16::MAX)) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_levels() {
+        assert_eq!(u8::from_level(300.0), u8::MAX);
+        assert_eq!(u8::from_level(12.7), 12);
+        assert_eq!(u8::quantize(12.7), 13);
+        assert_eq!(u16::from_level(-5.0), 0);
+        assert_eq!(u16::quantize(1000.5), 1001);
+        assert_eq!(u32::from_level(u32::FULL_SCALE), u32::MAX);
+        assert_eq!(f32::from_level(2.5).to_level(), 2.5);
+    }
+
+    #[test]
+    fn saturate_samples() {
+        assert_eq!(CanvasPixel::saturating_add(200u8, 100), u8::MAX);
+        assert_eq!(CanvasPixel::saturating_add(60000u16, 6000), u16::MAX);
+        assert_eq!(CanvasPixel::saturating_add(u32::MAX, 1), u32::MAX);
+        assert_eq!(CanvasPixel::saturating_add(0.75f32, 0.5), 1.25);
+    }
+
+    #[test]
+    fn convert_to_u16() {
+        assert_eq!(u8::MAX.to_u16(), u16::MAX);
+        assert_eq!(0x80u8.to_u16(), 0x8080);
+        assert_eq!(1234u16.to_u16(), 1234);
+        assert_eq!(u32::MAX.to_u16(), u16::MAX);
+        assert_eq!(0x1234_5678u32.to_u16(), 0x1234);
+        assert_eq!(1.0f32.to_u16(), u16::MAX);
+        assert_eq!(2.0f32.to_u16(), u16::MAX);
+        assert_eq!(0.5f32.to_u16(), 32767);
+        assert_eq!((-1.0f32).to_u16(), 0);
+    }
+}
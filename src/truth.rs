@@ -0,0 +1,388 @@
+//! Planetarium
+//! ===========
+//!
+//! Ground truth light spot parameters export
+//! -----------------------------------------
+//!
+//! Defines the public light spot parameters structure `SpotInfo`,
+//! the ground truth data format enum `GroundTruthFormat`,
+//! the image metadata structure `ImageMetadata`
+//! and the ground truth export methods for `Canvas`.
+
+use std::io::Write;
+
+use crate::{Canvas, CanvasPixel, EncoderError, Pixel, Point, SpotId, SpotShape, Transform};
+
+/// Rendered light spot parameters
+///
+/// Contains the exact light spot rendering parameters
+/// to be used as the ground truth data for evaluating
+/// the spot centroiding and photometry algorithms.
+///
+/// Usage
+/// -----
+///
+/// ```
+/// use planetarium::{Canvas, SpotShape, Transform};
+///
+/// let mut c = Canvas::new(256, 256);
+///
+/// let spot = c.add_spot((100.5, 130.25), SpotShape::default().scale(2.5), 0.5);
+///
+/// c.set_view_transform(Transform::default().translate((10.0, -5.0)));
+/// c.set_brightness(1.5);
+///
+/// let info = c.spot_info(spot).unwrap();
+///
+/// assert_eq!(info.position, (110.5, 125.25));
+/// assert_eq!(info.intensity, 0.75);
+/// assert!(info.visible);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SpotInfo {
+    /// Light spot descriptor
+    pub id: SpotId,
+    /// Canvas coordinates of the spot centroid after the view transform
+    pub position: Point,
+    /// Effective peak intensity
+    pub intensity: f32,
+    /// Spot shape definition matrix
+    pub shape: SpotShape,
+    /// Effective spot radius projected onto the X and Y axes
+    pub radius: (f32, f32),
+    /// Spot bounding box intersects the canvas rectangle
+    pub visible: bool,
+}
+
+/// Ground truth data export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GroundTruthFormat {
+    /// JSON array of light spot objects
+    Json,
+    /// CSV table with a header line and a light spot per line
+    Csv,
+}
+
+/// Canvas rendering parameters embedded into the exported images
+///
+/// The image metadata is stored in the PNG image text chunks
+/// when enabled by the `metadata` export option and can be parsed back
+/// with `ImageMetadata::read_png()`.
+#[derive(Debug, Clone)]
+pub struct ImageMetadata {
+    /// Canvas dimensions as `(width, height)`
+    pub dimensions: (u32, u32),
+    /// Background light level
+    pub background: Pixel,
+    /// Global brightness level
+    pub brightness: f32,
+    /// View coordinate transformation
+    pub transform: Transform,
+    /// Rendered light spot parameters
+    pub spots: Vec<SpotInfo>,
+}
+
+/// CSV ground truth table header line
+pub(crate) const CSV_HEADER: &str = "id,x,y,intensity,xx,xy,yx,yy,rx,ry,visible";
+
+/// Formats a floating point number as a JSON value.
+///
+/// JSON does not support non-finite numbers, so these become `null`.
+fn json_number(v: f32) -> String {
+    if v.is_finite() {
+        v.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+impl SpotInfo {
+    /// Formats the light spot parameters as a JSON object.
+    fn json_object(self) -> String {
+        let num = json_number;
+        let s = &self.shape;
+
+        format!(
+            "{{\"id\": {}, \"x\": {}, \"y\": {}, \"intensity\": {}, \
+            \"shape\": [[{}, {}], [{}, {}]], \"radius\": [{}, {}], \"visible\": {}}}",
+            self.id,
+            num(self.position.0),
+            num(self.position.1),
+            num(self.intensity),
+            num(s.xx),
+            num(s.xy),
+            num(s.yx),
+            num(s.yy),
+            num(self.radius.0),
+            num(self.radius.1),
+            self.visible
+        )
+    }
+
+    /// Formats the light spot parameters as a CSV table line.
+    pub(crate) fn csv_line(self) -> String {
+        let s = &self.shape;
+
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            self.id,
+            self.position.0,
+            self.position.1,
+            self.intensity,
+            s.xx,
+            s.xy,
+            s.yx,
+            s.yy,
+            self.radius.0,
+            self.radius.1,
+            self.visible
+        )
+    }
+}
+
+impl SpotInfo {
+    /// Parses the light spot parameters from a CSV table line.
+    #[cfg(feature = "png")]
+    pub(crate) fn parse_csv_line(line: &str) -> Option<SpotInfo> {
+        let mut fields = line.split(',');
+        let mut next = || fields.next();
+
+        let id = next()?.parse().ok()?;
+        let mut nums = [0.0f32; 9];
+
+        for v in &mut nums {
+            *v = next()?.parse().ok()?;
+        }
+
+        let visible = next()?.parse().ok()?;
+
+        if next().is_some() {
+            return None;
+        }
+
+        let [x, y, intensity, xx, xy, yx, yy, rx, ry] = nums;
+
+        Some(SpotInfo {
+            id,
+            position: (x, y),
+            intensity,
+            shape: SpotShape { xx, xy, yx, yy },
+            radius: (rx, ry),
+            visible,
+        })
+    }
+}
+
+/// Formats the light spot parameters as a CSV table with a header line.
+pub(crate) fn spots_csv(spots: impl Iterator<Item = SpotInfo>) -> String {
+    let mut text = String::from(CSV_HEADER);
+    text.push('\n');
+
+    for s in spots {
+        text.push_str(&s.csv_line());
+        text.push('\n');
+    }
+
+    text
+}
+
+impl<P: CanvasPixel> Canvas<P> {
+    /// Collects the canvas rendering parameters and the rendered
+    /// light spot parameters.
+    #[must_use]
+    pub fn image_metadata(&self) -> ImageMetadata {
+        ImageMetadata {
+            dimensions: (self.width, self.height),
+            background: self.background.to_u16(),
+            brightness: self.brightness,
+            transform: self.transform,
+            spots: (0..self.spots.len())
+                .filter_map(|id| self.spot_info(id))
+                .collect(),
+        }
+    }
+
+    /// Collects the rendered light spot parameters.
+    ///
+    /// The spot position and intensity are calculated in the same way
+    /// as the `spot_position()` and `spot_intensity()` methods do.
+    #[must_use]
+    pub fn spot_info(&self, spot: SpotId) -> Option<SpotInfo> {
+        let position = self.spot_position(spot)?;
+        let intensity = self.spot_intensity(spot)?;
+        let shape = self.spots[spot].shape;
+
+        Some(SpotInfo {
+            id: spot,
+            position,
+            intensity,
+            shape,
+            radius: shape.effective_radius_xy(),
+            visible: self.is_spot_visible(spot),
+        })
+    }
+
+    /// Exports the rendered parameters of all light spots
+    /// in the requested ground truth data format.
+    #[must_use]
+    pub fn export_ground_truth(&self, format: GroundTruthFormat) -> String {
+        let spots = (0..self.spots.len()).filter_map(|id| self.spot_info(id));
+
+        match format {
+            GroundTruthFormat::Json => {
+                let objects: Vec<String> = spots.map(SpotInfo::json_object).collect();
+
+                if objects.is_empty() {
+                    "[]\n".to_string()
+                } else {
+                    format!("[\n  {}\n]\n", objects.join(",\n  "))
+                }
+            }
+            GroundTruthFormat::Csv => spots_csv(spots),
+        }
+    }
+
+    /// Exports the rendered parameters of all light spots
+    /// in the requested ground truth data format into a caller-provided writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncoderError::IoError`] if writing to `writer` fails.
+    pub fn export_ground_truth_to<W: Write>(
+        &self,
+        format: GroundTruthFormat,
+        mut writer: W,
+    ) -> Result<(), EncoderError> {
+        writer.write_all(self.export_ground_truth(format).as_bytes())?;
+
+        Ok(writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transform;
+
+    /// Creates a 64x64 canvas with a visible and an invisible spot.
+    fn mkcanvas() -> Canvas {
+        let mut c = Canvas::new(64, 64);
+
+        c.add_spot((10.5, 20.25), SpotShape::default().scale(2.0), 0.5);
+        c.add_spot((-30.0, 40.0), SpotShape::from((1.5, 1.0)), 0.25);
+
+        c.set_view_transform(Transform::default().translate((2.0, -4.0)));
+        c
+    }
+
+    #[test]
+    fn get_spot_info() {
+        let c = mkcanvas();
+
+        let info = c.spot_info(0).unwrap();
+        assert_eq!(info.id, 0);
+        assert_eq!(info.position, (12.5, 16.25));
+        assert_eq!(info.intensity, 0.5);
+        assert_eq!(
+            info.radius,
+            SpotShape::default().scale(2.0).effective_radius_xy()
+        );
+        assert!(info.visible);
+
+        let info = c.spot_info(1).unwrap();
+        assert_eq!(info.position, (-28.0, 36.0));
+        assert!(!info.visible);
+
+        assert!(c.spot_info(2).is_none());
+    }
+
+    #[test]
+    fn export_csv_ground_truth() {
+        let text = mkcanvas().export_ground_truth(GroundTruthFormat::Csv);
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].starts_with("0,12.5,16.25,0.5,2,0,0,2,"));
+        assert!(lines[1].ends_with(",true"));
+        assert!(lines[2].starts_with("1,-28,36,0.25,1.5,0,0,1,"));
+        assert!(lines[2].ends_with(",false"));
+    }
+
+    #[test]
+    fn export_json_ground_truth() {
+        let mut c = mkcanvas();
+
+        let text = c.export_ground_truth(GroundTruthFormat::Json);
+        assert!(
+            text.starts_with("[\n  {\"id\": 0, \"x\": 12.5, \"y\": 16.25, \"intensity\": 0.5, ")
+        );
+        assert!(text.contains("\"shape\": [[1.5, 0], [0, 1]]"));
+        assert!(text.ends_with("\"visible\": false}\n]\n"));
+
+        c.set_brightness(f32::INFINITY);
+        let text = c.export_ground_truth(GroundTruthFormat::Json);
+        assert!(text.contains("\"intensity\": null"));
+
+        let empty = Canvas::new(16, 16);
+        assert_eq!(empty.export_ground_truth(GroundTruthFormat::Json), "[]\n");
+    }
+
+    #[test]
+    fn get_image_metadata() {
+        let mut c = mkcanvas();
+        c.set_background(1234);
+        c.set_brightness(0.75);
+
+        let meta = c.image_metadata();
+
+        assert_eq!(meta.dimensions, (64, 64));
+        assert_eq!(meta.background, 1234);
+        assert_eq!(meta.brightness, 0.75);
+        assert_eq!(meta.transform.to_string(), "[[1, 0, 2], [0, 1, -4]]");
+        assert_eq!(meta.spots.len(), 2);
+        assert_eq!(meta.spots[1].intensity, 0.1875);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn parse_csv_lines() {
+        let c = mkcanvas();
+        let text = c.export_ground_truth(GroundTruthFormat::Csv);
+
+        for (id, line) in text.lines().skip(1).enumerate() {
+            let s = SpotInfo::parse_csv_line(line).unwrap();
+            let info = c.spot_info(id).unwrap();
+
+            assert_eq!(s.id, info.id);
+            assert_eq!(s.position, info.position);
+            assert_eq!(s.intensity, info.intensity);
+            assert_eq!(s.shape.to_string(), info.shape.to_string());
+            assert_eq!(s.radius, info.radius);
+            assert_eq!(s.visible, info.visible);
+        }
+
+        assert!(SpotInfo::parse_csv_line(CSV_HEADER).is_none());
+        assert!(SpotInfo::parse_csv_line("1,2,3").is_none());
+    }
+
+    #[test]
+    fn export_ground_truth_to_writer() {
+        let c = mkcanvas();
+
+        let mut buf = Vec::new();
+        c.export_ground_truth_to(GroundTruthFormat::Csv, &mut buf)
+            .unwrap();
+        assert_eq!(
+            buf,
+            c.export_ground_truth(GroundTruthFormat::Csv).as_bytes()
+        );
+
+        let mut bytes = [0u8; 10];
+        assert_eq!(
+            c.export_ground_truth_to(GroundTruthFormat::Json, &mut bytes[..]),
+            Err(EncoderError::IoError(std::io::ErrorKind::WriteZero))
+        );
+    }
+}
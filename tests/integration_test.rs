@@ -191,3 +191,30 @@ fn export_sub_png16bpp() {
     let golden_img = include_bytes!("test_sub_16bpp.png");
     assert_eq!(img, golden_img);
 }
+
+#[test]
+fn export_raw10bpp_to_writer() {
+    let mut img = Vec::new();
+
+    mkimage()
+        .export_image_to(ImageFormat::RawLinear10BppLE, &mut img)
+        .unwrap();
+
+    let golden_img = include_bytes!("test_10bpp.raw");
+    assert_eq!(img, golden_img);
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn export_window_png16bpp_to_writer() {
+    let wnd = Window::new(32, 16).at(5, 8);
+
+    let mut img = Vec::new();
+
+    mkimage()
+        .export_window_image_to(wnd, ImageFormat::PngLinear16Bpp, &mut img)
+        .unwrap();
+
+    let golden_img = include_bytes!("test_wnd_16bpp.png");
+    assert_eq!(img, golden_img);
+}